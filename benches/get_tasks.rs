@@ -0,0 +1,65 @@
+//! Load-time benchmark for `db::get_tasks`.
+//!
+//! Run with `cargo bench --bench get_tasks`. Set `SAKD_BENCH_BUDGET_MS` to make
+//! the run fail when loading 100k tasks takes longer than the given budget.
+
+use rusqlite::Connection;
+use sakd::db;
+use std::time::{Duration, Instant};
+
+const SIZES: [usize; 2] = [10_000, 100_000];
+const RUNS: u32 = 5;
+
+fn seed(conn: &Connection, count: usize) {
+    let tx = conn.unchecked_transaction().unwrap();
+    {
+        let mut task_stmt = tx
+            .prepare("INSERT INTO tasks (title, is_done, limit_at, description, position) VALUES (?, ?, ?, ?, ?)")
+            .unwrap();
+        let mut tag_stmt = tx.prepare("INSERT INTO task_tags (task_id, tag) VALUES (?, ?)").unwrap();
+        let base = chrono::Utc::now();
+
+        for i in 0..count {
+            let limit = (i % 3 != 0).then(|| (base + chrono::Duration::minutes(i as i64)).to_rfc3339());
+            task_stmt
+                .execute(rusqlite::params![format!("Task {}", i), i % 5 == 0, limit, "benchmark task", i as i64 + 1])
+                .unwrap();
+            let id = tx.last_insert_rowid();
+            for tag in ["work", &format!("project-{}", i % 50), &format!("sprint-{}", i % 7)] {
+                tag_stmt.execute(rusqlite::params![id, tag]).unwrap();
+            }
+        }
+    }
+    tx.commit().unwrap();
+}
+
+fn main() {
+    let budget = std::env::var("SAKD_BENCH_BUDGET_MS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_millis);
+
+    for size in SIZES {
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_schema(&conn).unwrap();
+        seed(&conn, size);
+
+        let mut best = Duration::MAX;
+        for _ in 0..RUNS {
+            let start = Instant::now();
+            let tasks = db::get_tasks(&conn).unwrap();
+            best = best.min(start.elapsed());
+            assert_eq!(tasks.len(), size);
+        }
+
+        println!("get_tasks/{:<7} best of {}: {:>8.2?}", size, RUNS, best);
+
+        if let Some(budget) = budget
+            && size == *SIZES.last().unwrap()
+            && best > budget
+        {
+            eprintln!("get_tasks/{} exceeded budget of {:?}", size, budget);
+            std::process::exit(1);
+        }
+    }
+}
@@ -8,9 +8,7 @@ fn main() {
         process::exit(1);
     });
 
-    loop {
-        match tui::run_tui(&conn).expect("TUI error") {
-            tui::TuiEvent::Quit => break,
-        }
+    match tui::run_tui(&conn).expect("TUI error") {
+        tui::TuiEvent::Quit => {}
     }
 }
@@ -1,12 +1,19 @@
 use clap::Parser;
-use sakd::cli::{Cli, Commands};
+use sakd::cli::{Cli, Commands, ConfigCommands, TagCommands};
 use sakd::db;
+use sakd::ics;
+use sakd::report;
+use sakd::score;
+use sakd::stats;
 use sakd::utils;
 use inquire::{Confirm, Select, Text};
 use std::process;
-use chrono::{DateTime, Utc, Local, NaiveDate, NaiveTime, NaiveDateTime, TimeZone};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Utc};
 use colored::*;
 use unicode_width::UnicodeWidthStr;
+use std::collections::HashMap;
+use tabled::builder::Builder;
+use tabled::settings::{object::Columns, Alignment, Modify, Style};
 
 fn main() {
     let cli = Cli::parse();
@@ -14,12 +21,52 @@ fn main() {
         eprintln!("Failed to initialize database: {}", e);
         process::exit(1);
     });
+    let zone = db::display_zone(&conn).unwrap();
 
     match cli.command {
-        Some(Commands::Add { title, limit, description, tags }) => {
-            let title = title.unwrap_or_else(|| {
-                Text::new("Task title:").prompt().unwrap_or_else(|_| process::exit(0))
-            });
+        Some(Commands::Add { title: Some(line), limit, tz, scheduled, wait, description, priority, tags, dep, estimate }) => {
+            // One-line quick add; parts given as flags aren't read from the line
+            let given = utils::QuickAddGiven {
+                limit: limit.is_some(),
+                tags: tags.is_some(),
+                priority: priority.is_some(),
+                estimate: estimate.is_some(),
+            };
+            let parsed = utils::parse_quick_add_except(&line, zone, given);
+            if parsed.title.is_empty() {
+                eprintln!("Task title is missing in: {}", line);
+                process::exit(1);
+            }
+
+            let mut task = db::Task::new(&parsed.title);
+            task.limit = match limit {
+                Some(s) => Some(parse_limit_arg(&s)),
+                None => parsed.limit,
+            };
+            if let Some(tz) = tz {
+                task.limit = task.limit.map(|l| l.rezone(parse_tz_arg(&tz), zone));
+            }
+            task.priority = priority.map_or(parsed.priority, |p| parse_priority_arg(&p));
+            task.tags = tags.map_or(parsed.tags, |t| utils::parse_tags(&t));
+            task.scheduled = scheduled.and_then(|s| parse_day_arg(&s));
+            task.wait_until = wait.and_then(|w| parse_day_arg(&w));
+            task.description = description;
+            task.dependencies = dep.map(|d| parse_dep_arg(&conn, None, &d)).unwrap_or_default();
+            task.estimate = estimate.map_or(parsed.estimate, |e| parse_estimate_arg(&e));
+
+            // Show what was understood
+            print_quick_add_preview(&conn, &task, zone);
+            db::add_task(&conn, &task).unwrap();
+            println!("Task added: {}\n", task.title);
+        }
+        Some(Commands::Add { title: None, limit, tz, scheduled, wait, description, priority, tags, dep, estimate }) => {
+            let title = Text::new("Task title:").prompt().unwrap_or_else(|_| process::exit(0));
+
+            let priority_val = if let Some(p) = priority {
+                parse_priority_arg(&p)
+            } else {
+                prompt_priority(db::Priority::None)
+            };
             
             let tags_val = if let Some(t) = tags {
                 utils::parse_tags(&t)
@@ -28,11 +75,13 @@ fn main() {
                 utils::parse_tags(&ans)
             };
 
-            let limit_dt = if limit.is_some() {
-                limit.and_then(|s| DateTime::parse_from_rfc3339(&s).ok().map(|dt| dt.with_timezone(&Utc)))
-            } else {
-                prompt_limit(None)
+            let mut limit_dt = match limit {
+                Some(s) => Some(parse_limit_arg(&s)),
+                None => prompt_limit(None, zone),
             };
+            if let Some(tz) = tz {
+                limit_dt = limit_dt.map(|l| l.rezone(parse_tz_arg(&tz), zone));
+            }
 
             let description = if description.is_some() {
                 description
@@ -41,60 +90,409 @@ fn main() {
                 if desc.is_empty() { None } else { Some(desc) }
             };
             
-            db::add_task(&conn, &title, limit_dt, description, tags_val).unwrap();
+            let mut task = db::Task::new(&title);
+            task.limit = limit_dt;
+            task.description = description;
+            task.priority = priority_val;
+            task.tags = tags_val;
+            task.scheduled = scheduled.and_then(|s| parse_day_arg(&s));
+            task.wait_until = wait.and_then(|w| parse_day_arg(&w));
+            task.dependencies = dep.map(|d| parse_dep_arg(&conn, None, &d)).unwrap_or_default();
+            task.estimate = estimate.and_then(|e| parse_estimate_arg(&e));
+            db::add_task(&conn, &task).unwrap();
             println!("Task added: {}\n", title);
         }
         Some(Commands::Done { id }) => {
             let id = resolve_id(&conn, id);
-            if let Some(id) = id {
-                if let Some(mut task) = db::get_task(&conn, id).unwrap() {
-                    task.is_done = true;
-                    db::update_task(&conn, &task).unwrap();
-                    println!("Task marked as done.\n");
+            if let Some(id) = id
+                && let Some(mut task) = db::get_task(&conn, id).unwrap() {
+                task.is_done = true;
+                db::update_task(&conn, &task).unwrap();
+                println!("Task marked as done.\n");
+            }
+        }
+        Some(Commands::List { all, done, waiting, tag, before, after, sort, limit, offset }) => {
+            let hide_waiting = !(all || done || waiting);
+            let order = sort.map(|s| utils::parse_sort(&s).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                process::exit(1);
+            }));
+            let query = db::TaskQuery::new()
+                .tag(tag)
+                .done(if done { Some(true) } else if all { None } else { Some(false) })
+                .actionable_on(hide_waiting.then(|| zone.today()))
+                .due_before(before.map(|d| parse_date_arg(&d, "", zone)))
+                .due_after(after.map(|d| parse_date_arg(&d, "00:00", zone)))
+                .order(order.unwrap_or_default())
+                .limit(limit)
+                .offset(offset);
+            let tasks = db::query_tasks(&conn, &query).unwrap();
+
+            print_tasks(&conn, &tasks, all || done, zone);
+            println!();
+        }
+        Some(Commands::Start { id }) => {
+            if let Some(id) = resolve_id(&conn, id)
+                && let Some(task) = db::get_task(&conn, id).unwrap() {
+                let now = Utc::now();
+                if let Some(stopped) = db::start_timer(&conn, id, now).unwrap() {
+                    print_stopped(&conn, &stopped, now);
                 }
+                println!("Started tracking {} {}.\n", task.id, task.title.bold());
             }
         }
-        Some(Commands::List { all, tag }) => {
-            let mut tasks = db::get_tasks(&conn).unwrap();
-            
-            if let Some(t) = tag {
-                tasks.retain(|task| task.tags.iter().any(|tag_str| tag_str.contains(&t)));
+        Some(Commands::Stop) => {
+            let now = Utc::now();
+            match db::stop_timer(&conn, now).unwrap() {
+                Some(stopped) => print_stopped(&conn, &stopped, now),
+                None => println!("No timer is running."),
+            }
+            println!();
+        }
+        Some(Commands::Status) => {
+            match db::running_timer(&conn).unwrap() {
+                Some(entry) => {
+                    let now = Utc::now();
+                    let task = db::get_task(&conn, entry.task_id).unwrap().unwrap();
+                    println!(
+                        "Tracking {} {} for {} (since {}).",
+                        task.id,
+                        task.title.bold(),
+                        utils::format_duration(entry.duration(now)).cyan(),
+                        zone.naive(entry.started).format("%H:%M"),
+                    );
+                }
+                None => println!("No timer is running."),
+            }
+            println!();
+        }
+        Some(Commands::Report { from, to, by, csv }) => {
+            let today = zone.today();
+            let from = from.and_then(|d| parse_day_arg(&d)).unwrap_or_else(|| utils::week_start(today));
+            let to = to.and_then(|d| parse_day_arg(&d)).unwrap_or(today);
+            let day_start = |day: NaiveDate| zone.resolve(day.and_hms_opt(0, 0, 0).unwrap());
+            let (start, end) = (day_start(from), day_start(to + Duration::days(1)));
+
+            let entries = db::get_time_entries_between(&conn, start, end).unwrap();
+            let completed = db::TaskQuery::new().completed_between(Some((start, end)));
+            let mut tasks: HashMap<i64, db::Task> = db::query_tasks(&conn, &completed).unwrap()
+                .into_iter()
+                .map(|t| (t.id, t))
+                .collect();
+            for entry in &entries {
+                if !tasks.contains_key(&entry.task_id)
+                    && let Some(task) = db::get_task(&conn, entry.task_id).unwrap() {
+                    tasks.insert(task.id, task);
+                }
             }
 
-            print_tasks(&tasks, all);
+            let rows = report::build(by, &entries, &tasks, (from, to), zone, Utc::now());
+            if csv {
+                print!("{}", report::to_csv(by, &rows));
+            } else {
+                println!("{} {} - {}", "Report".cyan().bold(), from.format("%Y-%m-%d"), to.format("%Y-%m-%d"));
+                print_report(by, &rows);
+                println!();
+            }
+        }
+        Some(Commands::Stats { days }) => {
+            let tasks = db::get_tasks(&conn).unwrap();
+            print_stats(&stats::build(&tasks, days as usize, zone, Utc::now()));
+            println!();
+        }
+        Some(Commands::Burndown { tag, from, to }) => {
+            let tasks = db::query_tasks(&conn, &db::TaskQuery::new().tag(Some(tag.clone()))).unwrap();
+            if tasks.is_empty() {
+                eprintln!("No tasks tagged #{}", tag);
+                process::exit(1);
+            }
+            let today = zone.today();
+            let to = to.and_then(|d| parse_day_arg(&d)).unwrap_or(today);
+            let from = from.and_then(|d| parse_day_arg(&d)).unwrap_or_else(|| stats::burndown_start(&tasks, zone, to));
+            if from > to {
+                eprintln!("--from must not be after --to");
+                process::exit(1);
+            }
+            println!("{} #{}  {} - {}", "Burndown".cyan().bold(), tag, from.format("%Y-%m-%d"), to.format("%Y-%m-%d"));
+            print_burndown(&stats::burndown(&tasks, (from, to), zone));
             println!();
         }
         Some(Commands::Remove { id }) => {
             let id = resolve_id(&conn, id);
-            if let Some(id) = id {
-                if Confirm::new("Are you sure you want to remove this task?").with_default(false).prompt().unwrap_or(false) {
-                    db::delete_task(&conn, id).unwrap();
-                    println!("Task removed.\n");
+            if let Some(id) = id
+                && Confirm::new("Are you sure you want to remove this task?").with_default(false).prompt().unwrap_or(false) {
+                db::delete_task(&conn, id).unwrap();
+                println!("Task removed.\n");
+            }
+        }
+        Some(Commands::Move { id, before, after }) => {
+            let target = before.or(after).unwrap();
+            for id in [id, target] {
+                if db::get_task(&conn, id).unwrap().is_none() {
+                    eprintln!("No task with ID: {}", id);
+                    process::exit(1);
                 }
             }
+            db::move_task(&conn, id, target, after.is_some()).unwrap();
+            println!("Task moved.\n");
         }
         Some(Commands::Show { id }) => {
             let id = resolve_id(&conn, id);
-            if let Some(id) = id {
-                if let Some(task) = db::get_task(&conn, id).unwrap() {
-                    println!("\n{}", "--- Task Details ---".cyan().bold());
-                    println!("{}: {}", "ID".bold(), task.id);
-                    println!("{}: {}", "Tags".bold(), task.tags.join(", "));
-                    println!("{}: {}", "Title".bold(), task.title);
-                    println!("{}: {}", "Done".bold(), if task.is_done { "Yes".green() } else { "No".red() });
-                    println!("{}: {}", "Limit".bold(), format_limit_color(task.limit));
-                    println!("{}: {}", "Description".bold(), task.description.unwrap_or_else(|| "None".to_string()));
-                    println!();
+            if let Some(id) = id
+                && let Some(task) = db::get_task(&conn, id).unwrap() {
+                print_details(&conn, &task, zone);
+                println!();
+            }
+        }
+        Some(Commands::Edit { id, tz, scheduled, wait, state, dep, estimate })
+            if tz.is_some() || scheduled.is_some() || wait.is_some() || state.is_some() || dep.is_some() || estimate.is_some() => {
+            // Change only what was given, without prompting
+            if let Some(id) = resolve_id(&conn, id)
+                && let Some(mut task) = db::get_task(&conn, id).unwrap() {
+                if let Some(tz) = tz {
+                    // Read the deadline's wall time in another zone
+                    let tz = parse_tz_arg(&tz);
+                    match task.limit {
+                        Some(limit) => task.limit = Some(limit.rezone(tz, zone)),
+                        None => println!("Task {} has no limit.", id),
+                    }
+                }
+                if let Some(s) = scheduled {
+                    task.scheduled = parse_day_arg(&s);
                 }
+                if let Some(w) = wait {
+                    task.wait_until = parse_day_arg(&w);
+                }
+                if let Some(state) = state {
+                    let workflow = db::workflow(&conn).unwrap();
+                    if !workflow.set_state(&mut task, &state) {
+                        eprintln!("Unknown state '{}'. States: {}", state, workflow.states.join(", "));
+                        process::exit(1);
+                    }
+                }
+                if let Some(dep) = dep {
+                    task.dependencies = parse_dep_arg(&conn, Some(task.id), &dep);
+                }
+                if let Some(estimate) = estimate {
+                    task.estimate = parse_estimate_arg(&estimate);
+                }
+                db::update_task(&conn, &task).unwrap();
+                print_details(&conn, &task, zone);
+                println!();
             }
         }
-        Some(Commands::Edit { id }) => {
+        Some(Commands::Edit { id, .. }) => {
             let id = resolve_id(&conn, id);
             if let Some(id) = id {
-                interactive_edit(&conn, id);
+                interactive_edit(&conn, id, zone);
                 println!();
             }
         }
+        Some(Commands::Plan { day }) => {
+            let today = zone.today();
+            let day = day.and_then(|d| parse_day_arg(&d)).unwrap_or(today);
+            let open = db::TaskQuery::new().done(Some(false)).actionable_on(Some(day));
+            let end_of_day = zone.resolve(day.and_hms_opt(23, 59, 59).unwrap());
+            let mut tasks = db::query_tasks(&conn, &open.clone().scheduled_by(Some(day))).unwrap();
+            // Due that day, or overdue when planning today
+            for task in db::query_tasks(&conn, &open.due_before(Some(end_of_day))).unwrap() {
+                let due_day = task.limit.is_some_and(|l| l.date(zone) == day || day == today);
+                if due_day && !tasks.iter().any(|t| t.id == task.id) {
+                    tasks.push(task);
+                }
+            }
+            print_plan(&conn, day, &tasks, db::daily_capacity(&conn).unwrap(), zone);
+            println!();
+        }
+        Some(Commands::Next { count, tag }) => {
+            let query = db::TaskQuery::new().tag(tag).done(Some(false)).actionable_on(Some(zone.today()));
+            let tasks = db::query_tasks(&conn, &query).unwrap();
+            let ctx = score::Context { now: Utc::now(), zone, open_dependencies: db::open_dependencies(&conn).unwrap() };
+            let scores = score::rank(&tasks, &ctx);
+            if scores.is_empty() {
+                println!("Nothing to do.\n");
+                return;
+            }
+
+            for (n, s) in scores.iter().take(count.max(1)).enumerate() {
+                let task = tasks.iter().find(|t| t.id == s.task_id).unwrap();
+                let heading = if n == 0 { "Next:".cyan().bold() } else { format!("{}.", n + 1).normal() };
+                println!("{} {} {}  {}", heading, task.id, task.title.bold(), format!("(score {})", s.total).bright_black());
+                print_factors(&s.factors);
+            }
+            println!();
+        }
+        Some(Commands::Today) => {
+            let today = zone.today();
+            let open = db::TaskQuery::new().done(Some(false)).actionable_on(Some(today));
+            let plan = db::query_tasks(&conn, &open.clone().scheduled_by(Some(today))).unwrap();
+            let start = zone.resolve(today.and_hms_opt(0, 0, 0).unwrap());
+            let overdue = db::query_tasks(&conn, &open.clone().due_before(Some(start - Duration::seconds(1)))).unwrap();
+            let due = db::query_tasks(&conn, &open
+                .due_after(Some(start))
+                .due_before(Some(zone.resolve(today.and_hms_opt(23, 59, 59).unwrap())))).unwrap();
+
+            println!("{}", "Today's plan".cyan().bold());
+            print_tasks(&conn, &plan, false, zone);
+            if !overdue.is_empty() {
+                println!("\n{}", "Overdue".magenta().bold());
+                print_tasks(&conn, &overdue, false, zone);
+            }
+            println!("\n{}", "Due today".cyan().bold());
+            print_tasks(&conn, &due, false, zone);
+            println!();
+        }
+        Some(Commands::Agenda { days }) => {
+            let today = zone.today();
+            let last = today + Duration::days(days as i64 - 1);
+            let query = db::TaskQuery::new()
+                .done(Some(false))
+                .actionable_on(Some(today))
+                .due_before(Some(zone.resolve(last.and_hms_opt(23, 59, 59).unwrap())));
+            let tasks = db::query_tasks(&conn, &query).unwrap();
+            print_agenda(&conn, &tasks, days, zone);
+            println!();
+        }
+        Some(Commands::Cal { month }) => {
+            let today = zone.today();
+            let first = match month {
+                Some(m) => utils::parse_month(&m, today).unwrap_or_else(|| {
+                    eprintln!("Invalid month: {}", m);
+                    process::exit(1);
+                }),
+                None => today.with_day(1).unwrap(),
+            };
+            let last = utils::month_days(first).last().unwrap();
+            let query = db::TaskQuery::new()
+                .done(Some(false))
+                .due_after(Some(zone.resolve(first.and_hms_opt(0, 0, 0).unwrap())))
+                .due_before(Some(zone.resolve(last.and_hms_opt(23, 59, 59).unwrap())));
+            let mut counts: HashMap<NaiveDate, usize> = HashMap::new();
+            for task in db::query_tasks(&conn, &query).unwrap() {
+                if let Some(limit) = task.limit {
+                    *counts.entry(limit.date(zone)).or_default() += 1;
+                }
+            }
+            print_calendar(first, &counts, today);
+            println!();
+        }
+        Some(Commands::Tags { action }) => {
+            let counts = db::get_tag_counts(&conn).unwrap();
+            let exists = |tag: &str| counts.iter().any(|(t, _)| t == tag);
+
+            match action {
+                None => print_tags(&conn, &counts),
+                Some(TagCommands::Rename { old, new }) => {
+                    if !exists(&old) {
+                        println!("Tag '{}' not found.", old);
+                    } else if exists(&new) {
+                        println!("Tag '{}' already exists. Use `sakd tags merge {} {}` to combine them.", new, old, new);
+                    } else {
+                        let n = db::rename_tag(&conn, &old, &new).unwrap();
+                        println!("Renamed '{}' to '{}' on {} task(s).", old, new, n);
+                    }
+                }
+                Some(TagCommands::Merge { from, into }) => {
+                    let (from, into) = (utils::normalize_tag(&from), utils::normalize_tag(&into));
+                    if !exists(&from) {
+                        println!("Tag '{}' not found.", from);
+                    } else if utils::same_tag(&from, &into) {
+                        println!("Can't merge tag '{}' into itself.", from);
+                    } else {
+                        let n = db::merge_tags(&conn, &from, &into).unwrap();
+                        println!("Merged '{}' into '{}' on {} task(s).", from, into, n);
+                    }
+                }
+                Some(TagCommands::Delete { tag }) => {
+                    if !exists(&tag) {
+                        println!("Tag '{}' not found.", tag);
+                    } else if Confirm::new(&format!("Remove tag '{}' from all tasks?", tag)).with_default(false).prompt().unwrap_or(false) {
+                        let n = db::delete_tag(&conn, &tag).unwrap();
+                        println!("Tag '{}' removed from {} task(s).", tag, n);
+                    }
+                }
+                Some(TagCommands::SetColor { tag, color }) => {
+                    let tag = utils::normalize_tag(&tag);
+                    match optional_setting(color) {
+                        None => {
+                            update_tag_meta(&conn, &tag, |m| m.color = None);
+                            println!("Color of '{}' reset.", tag);
+                        }
+                        Some(c) => match utils::normalize_color(&c) {
+                            Some(c) => {
+                                update_tag_meta(&conn, &tag, |m| m.color = Some(c.clone()));
+                                println!("Tag {} is now shown in {}.", format_tag(&db::get_tag_metas(&conn).unwrap(), &tag), c);
+                            }
+                            None => println!("Unknown color '{}'. Use one of {} or #rrggbb.", c, utils::TAG_COLORS.join(", ")),
+                        },
+                    }
+                }
+                Some(TagCommands::SetIcon { tag, icon }) => {
+                    let tag = utils::normalize_tag(&tag);
+                    update_tag_meta(&conn, &tag, |m| m.icon = optional_setting(icon));
+                    println!("Icon of '{}' updated.", tag);
+                }
+                Some(TagCommands::Describe { tag, description }) => {
+                    let tag = utils::normalize_tag(&tag);
+                    update_tag_meta(&conn, &tag, |m| m.description = optional_setting(description));
+                    println!("Description of '{}' updated.", tag);
+                }
+                Some(TagCommands::SetPriority { tag, priority }) => {
+                    let tag = utils::normalize_tag(&tag);
+                    let priority = parse_priority_arg(&priority);
+                    update_tag_meta(&conn, &tag, |m| m.default_priority = priority);
+                    println!("New tasks tagged '{}' get priority {:?}.", tag, priority);
+                }
+            }
+            println!();
+        }
+        Some(Commands::Export { all, output }) => {
+            let query = db::TaskQuery::new().done(if all { None } else { Some(false) });
+            let tasks = db::query_tasks(&conn, &query).unwrap();
+            let ics = ics::export(&tasks, Utc::now());
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, ics).unwrap_or_else(|e| {
+                        eprintln!("Failed to write {}: {}", path, e);
+                        process::exit(1);
+                    });
+                    println!("Exported {} task(s) to {}.\n", tasks.len(), path);
+                }
+                None => print!("{}", ics),
+            }
+        }
+        Some(Commands::Config { action }) => {
+            match action {
+                None => {
+                    let settings = db::get_settings(&conn).unwrap();
+                    for (key, description) in utils::SETTINGS {
+                        let value = settings.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+                        println!("  {} = {}  {}", key.bold(), value.unwrap_or_else(|| "(unset)".bright_black().to_string()), description.bright_black());
+                    }
+                }
+                Some(ConfigCommands::Get { key }) => match db::get_setting(&conn, &key).unwrap() {
+                    Some(value) => println!("{}", value),
+                    None => println!("'{}' is not set.", key),
+                },
+                Some(ConfigCommands::Set { key, value }) => match utils::validate_setting(&key, &value) {
+                    Ok(value) => {
+                        db::set_setting(&conn, &key, &value).unwrap();
+                        db::refresh_floating_limits(&conn).unwrap();
+                        println!("{} = {}", key, value);
+                    }
+                    Err(e) => println!("{}", e),
+                },
+                Some(ConfigCommands::Unset { key }) => {
+                    if db::unset_setting(&conn, &key).unwrap() {
+                        db::refresh_floating_limits(&conn).unwrap();
+                        println!("'{}' reset to its default.", key);
+                    } else {
+                        println!("'{}' is not set.", key);
+                    }
+                }
+            }
+            println!();
+        }
         None => {
             // Interactive mode if no command given
             loop {
@@ -105,44 +503,35 @@ fn main() {
                     "List" => {
                         let tasks = db::get_tasks(&conn).unwrap();
                         let all = Confirm::new("Show completed tasks?").with_default(false).prompt().unwrap_or(false);
-                        print_tasks(&tasks, all);
+                        print_tasks(&conn, &tasks, all, zone);
                     }
                     "Add" => {
-                        interactive_add(&conn);
+                        interactive_add(&conn, zone);
                     }
                     "Done" => {
-                        if let Some(id) = resolve_id(&conn, None) {
-                            if let Some(mut task) = db::get_task(&conn, id).unwrap() {
-                                task.is_done = true;
-                                db::update_task(&conn, &task).unwrap();
-                                println!("Task marked as done.");
-                            }
+                        if let Some(id) = resolve_id(&conn, None)
+                            && let Some(mut task) = db::get_task(&conn, id).unwrap() {
+                            task.is_done = true;
+                            db::update_task(&conn, &task).unwrap();
+                            println!("Task marked as done.");
                         }
                     }
                     "Show" => {
-                        if let Some(id) = resolve_id(&conn, None) {
-                            if let Some(task) = db::get_task(&conn, id).unwrap() {
-                                println!("\n{}", "--- Task Details ---".cyan().bold());
-                                println!("{}: {}", "ID".bold(), task.id);
-                                println!("{}: {}", "Title".bold(), task.title);
-                                println!("{}: {}", "Tags".bold(), task.tags.join(", "));
-                                println!("{}: {}", "Done".bold(), if task.is_done { "Yes".green() } else { "No".red() });
-                                println!("{}: {}", "Limit".bold(), format_limit_color(task.limit));
-                                println!("{}: {}", "Description".bold(), task.description.unwrap_or_else(|| "None".to_string()));
-                            }
+                        if let Some(id) = resolve_id(&conn, None)
+                            && let Some(task) = db::get_task(&conn, id).unwrap() {
+                            print_details(&conn, &task, zone);
                         }
                     }
                     "Edit" => {
                         if let Some(id) = resolve_id(&conn, None) {
-                            interactive_edit(&conn, id);
+                            interactive_edit(&conn, id, zone);
                         }
                     }
                     "Remove" => {
-                        if let Some(id) = resolve_id(&conn, None) {
-                            if Confirm::new("Are you sure?").with_default(false).prompt().unwrap_or(false) {
-                                db::delete_task(&conn, id).unwrap();
-                                println!("Task removed.");
-                            }
+                        if let Some(id) = resolve_id(&conn, None)
+                            && Confirm::new("Are you sure?").with_default(false).prompt().unwrap_or(false) {
+                            db::delete_task(&conn, id).unwrap();
+                            println!("Task removed.");
                         }
                     }
                     _ => break,
@@ -154,29 +543,36 @@ fn main() {
 }
 
 
-fn interactive_add(conn: &rusqlite::Connection) {
+fn interactive_add(conn: &rusqlite::Connection, zone: utils::DisplayZone) {
     let title = Text::new("Task title:").prompt().unwrap_or_default();
     if !title.is_empty() {
+        let mut task = db::Task::new(&title);
+        task.priority = prompt_priority(db::Priority::None);
+
         let tags_ans = Text::new("Tags (comma separated):").prompt().unwrap_or_default();
-        let tags = utils::parse_tags(&tags_ans);
+        task.tags = utils::parse_tags(&tags_ans);
 
-        let limit = prompt_limit(None);
+        task.limit = prompt_limit(None, zone);
         let desc = Text::new("Description:").prompt().unwrap_or_default();
-        let description = if desc.is_empty() { None } else { Some(desc) };
-        db::add_task(conn, &title, limit, description, tags).unwrap();
+        task.description = if desc.is_empty() { None } else { Some(desc) };
+        db::add_task(conn, &task).unwrap();
         println!("Task added.");
     }
 }
 
-fn interactive_edit(conn: &rusqlite::Connection, id: i64) {
+fn interactive_edit(conn: &rusqlite::Connection, id: i64, zone: utils::DisplayZone) {
     if let Some(mut task) = db::get_task(conn, id).unwrap() {
         task.title = Text::new("Title:").with_default(&task.title).prompt().unwrap_or(task.title);
+        task.priority = prompt_priority(task.priority);
         
         let tags_str = task.tags.join(", ");
         let tags_ans = Text::new("Tags (comma separated):").with_default(&tags_str).prompt().unwrap_or(tags_str);
         task.tags = utils::parse_tags(&tags_ans);
 
-        task.limit = prompt_limit(task.limit);
+        task.limit = prompt_limit(task.limit, zone);
+        task.scheduled = prompt_day("Scheduled:", task.scheduled);
+        task.wait_until = prompt_day("Wait until:", task.wait_until);
+        task.estimate = prompt_estimate(task.estimate);
         
         let current_desc = task.description.clone().unwrap_or_default();
         let desc = Text::new("Description:").with_default(&current_desc).prompt().unwrap_or(current_desc);
@@ -187,8 +583,53 @@ fn interactive_edit(conn: &rusqlite::Connection, id: i64) {
     }
 }
 
-fn prompt_limit(current: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
-    let current_local = current.map(|c| c.with_timezone(&Local));
+/// Asks for an estimate until one parses; only an empty answer clears it.
+fn prompt_estimate(current: Option<Duration>) -> Option<Duration> {
+    let current_str = current.map(utils::format_duration).unwrap_or_default();
+    loop {
+        let Ok(ans) = Text::new("Estimate:")
+            .with_help_message("e.g. 30m, 2h, 1h30m, 1d (empty for none)")
+            .with_default(&current_str)
+            .prompt()
+        else {
+            return current;
+        };
+        if ans.trim().is_empty() {
+            return None;
+        }
+        match utils::parse_duration(&ans) {
+            Some(estimate) => return Some(estimate),
+            None => eprintln!("Invalid estimate: {} (e.g. 30m, 2h, 1h30m, 1d)", ans),
+        }
+    }
+}
+
+/// Keeps `current` when the prompt is cancelled.
+fn prompt_priority(current: db::Priority) -> db::Priority {
+    let choices = [
+        (db::Priority::None, " "),
+        (db::Priority::Low, "Low"),
+        (db::Priority::Medium, "Medium"),
+        (db::Priority::High, "High"),
+    ];
+    let labels: Vec<&str> = choices.iter().map(|(_, label)| *label).collect();
+    Select::new("Priority:", labels)
+        .with_starting_cursor(choices.iter().position(|(p, _)| *p == current).unwrap_or(0))
+        .raw_prompt()
+        .map_or(current, |ans| choices[ans.index].0)
+}
+
+fn parse_priority_arg(s: &str) -> db::Priority {
+    utils::parse_priority(s).unwrap_or_else(|| {
+        eprintln!("Invalid priority: {} (use high, medium, low or none)", s);
+        process::exit(1);
+    })
+}
+
+/// Asks for a date and time; without a time the deadline is all-day.
+/// An existing deadline keeps its zone.
+fn prompt_limit(current: Option<db::Deadline>, display: utils::DisplayZone) -> Option<db::Deadline> {
+    let current_local = current.map(|c| c.own_local(display));
     let (default_date, date_help) = if let Some(local) = current_local {
         (local.format("%Y-%m-%d").to_string(), " (Enter to keep current)")
     } else {
@@ -196,7 +637,7 @@ fn prompt_limit(current: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
     };
 
     let date_str = Text::new("Date (YYYY-MM-DD/Shortcut):")
-        .with_help_message(&format!("Shortcuts: t (today), tm (tomorrow), 2d, 1w, mon-sun{}", date_help))
+        .with_help_message(&format!("Shortcuts: t, tm, 2d, 1w, 3m, mon-sun, next mon, this fri, in 3 days, eom, 明日, 来週月曜, 3日後{}", date_help))
         .with_default(&default_date)
         .prompt()
         .ok()?;
@@ -205,54 +646,150 @@ fn prompt_limit(current: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
         return None;
     }
 
-    let date = utils::parse_shortcut_date(&date_str).or_else(|| NaiveDate::parse_from_str(&date_str, "%Y-%m-%d").ok())?;
+    let date = utils::parse_date(&date_str)?;
 
-    let (default_time, time_help) = if let Some(local) = current_local {
-        (local.format("%H:%M").to_string(), " (Enter to keep current)")
-    } else {
-        ("23:59".to_string(), " (Enter for 23:59)")
+    let (default_time, time_help) = match current {
+        Some(db::Deadline::Day(_)) | None => (String::new(), " (Empty for all day)"),
+        Some(c) => (c.own_local(display).format("%H:%M").to_string(), " (Enter to keep current)"),
     };
 
     let time_str = Text::new("Time (HH:MM/Shortcut):")
-        .with_help_message(&format!("Shortcuts: last (23:59), morning (09:00), noon (12:00), 1h{}", time_help))
+        .with_help_message(&format!("Shortcuts: last (23:59), morning (09:00), noon (12:00), 1h, 午後3時, 夕方{}", time_help))
         .with_default(&default_time)
         .prompt()
         .ok()?;
 
-    let time = if time_str.trim().is_empty() {
-        NaiveTime::from_hms_opt(23, 59, 0).unwrap()
-    } else {
-        utils::parse_shortcut_time(&time_str).or_else(|| NaiveTime::parse_from_str(&time_str, "%H:%M").ok())
-            .unwrap_or_else(|| NaiveTime::from_hms_opt(23, 59, 0).unwrap())
+    let Some(time) = utils::parse_time(&time_str) else {
+        return Some(db::Deadline::Day(date));
     };
 
-    let dt = NaiveDateTime::new(date, time);
-    Local.from_local_datetime(&dt).single().map(|dt| dt.with_timezone(&Utc))
+    let naive = NaiveDateTime::new(date, time);
+    let zone = current.map_or(utils::DeadlineZone::Local, |c| c.zone());
+    if let Some(note) = utils::deadline_note(naive, zone, display) {
+        println!("{} {} {}", "Note:".yellow(), naive.format("%Y-%m-%d %H:%M"), note);
+    }
+    Some(db::Deadline::Floating(naive).rezone(zone, display))
 }
 
-fn format_limit_color(limit: Option<DateTime<Utc>>) -> String {
-    match limit {
-        Some(l) => {
-            let now = Utc::now();
-            let local_l = l.with_timezone(&Local);
-            let s = local_l.format("%Y-%m-%d %H:%M").to_string();
-            
-            if l < now {
-                s.magenta().bold().to_string()
-            } else if l < now + chrono::Duration::days(1) {
-                s.red().to_string()
-            } else if l < now + chrono::Duration::days(3) {
-                s.yellow().to_string()
-            } else if l < now + chrono::Duration::days(7) {
-                s.green().to_string()
-            } else {
-                s.bright_black().to_string()
+/// Asks for a day until one parses; only an empty answer clears it.
+fn prompt_day(message: &str, current: Option<chrono::NaiveDate>) -> Option<chrono::NaiveDate> {
+    let default = current.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_default();
+    loop {
+        let Ok(ans) = Text::new(message)
+            .with_help_message("Date or shortcut (Empty for none)")
+            .with_default(&default)
+            .prompt()
+        else {
+            return current;
+        };
+        if ans.trim().is_empty() {
+            return None;
+        }
+        match utils::parse_date(&ans) {
+            Some(day) => return Some(day),
+            None => eprintln!("Invalid date: {}", ans),
+        }
+    }
+}
+
+fn parse_date_arg(date_str: &str, time_str: &str, zone: utils::DisplayZone) -> DateTime<Utc> {
+    utils::parse_full_date_time(date_str, time_str, zone).unwrap_or_else(|| {
+        eprintln!("Invalid date: {}", date_str);
+        process::exit(1);
+    })
+}
+
+/// An RFC 3339 limit given with `--limit`.
+fn parse_limit_arg(s: &str) -> db::Deadline {
+    let dt = DateTime::parse_from_rfc3339(s).unwrap_or_else(|_| {
+        eprintln!("Invalid date: {} (e.g. 2026-01-16T20:00:00+09:00)", s);
+        process::exit(1);
+    });
+    db::Deadline::At(dt.with_timezone(&Utc), None)
+}
+
+/// A day given with `--scheduled`/`--wait`; "none" means no day.
+fn parse_day_arg(s: &str) -> Option<chrono::NaiveDate> {
+    optional_setting(s.to_string())?;
+    Some(utils::parse_date(s).unwrap_or_else(|| {
+        eprintln!("Invalid date: {}", s);
+        process::exit(1);
+    }))
+}
+
+/// Task ids given with `--dep` for `task` (`None` for a new task); "none"
+/// means no dependencies. Exits on dependencies that would make a cycle.
+fn parse_dep_arg(conn: &rusqlite::Connection, task: Option<i64>, s: &str) -> Vec<i64> {
+    if optional_setting(s.to_string()).is_none() {
+        return Vec::new();
+    }
+    let deps: Vec<i64> = s.split(',')
+        .map(|id| match id.trim().parse() {
+            Ok(id) if db::get_task(conn, id).unwrap().is_some() => id,
+            _ => {
+                eprintln!("No task with ID: {}", id.trim());
+                process::exit(1);
             }
+        })
+        .collect();
+    if let Some(id) = task
+        && let Some(dep) = db::dependency_cycle(conn, id, &deps).unwrap()
+    {
+        if dep == id {
+            eprintln!("Task {} can't depend on itself.", id);
+        } else {
+            eprintln!("Task {} already depends on task {}, directly or through other tasks.", dep, id);
         }
+        process::exit(1);
+    }
+    deps
+}
+
+/// An effort given with `--estimate`; "none" means no estimate.
+fn parse_estimate_arg(s: &str) -> Option<Duration> {
+    optional_setting(s.to_string())?;
+    Some(utils::parse_duration(s).unwrap_or_else(|| {
+        eprintln!("Invalid estimate: {} (e.g. 30m, 2h, 1h30m, 1d)", s);
+        process::exit(1);
+    }))
+}
+
+fn parse_tz_arg(s: &str) -> utils::DeadlineZone {
+    utils::DeadlineZone::parse(s).unwrap_or_else(|| {
+        eprintln!("Unknown time zone: {} (use an IANA name such as Asia/Tokyo, floating or local)", s);
+        process::exit(1);
+    })
+}
+
+fn format_limit_color(limit: Option<db::Deadline>, zone: utils::DisplayZone) -> String {
+    match limit {
+        Some(d) => paint_urgency(&d.format(zone, "%Y-%m-%d"), d.instant(zone)),
         None => "None".bright_black().to_string(),
     }
 }
 
+/// Colors `s` by how soon `l` is: overdue, within a day, 3 days, a week, or later.
+fn paint_urgency(s: &str, l: DateTime<Utc>) -> String {
+    let now = Utc::now();
+    if l < now {
+        s.magenta().bold().to_string()
+    } else if l < now + Duration::days(1) {
+        s.red().to_string()
+    } else if l < now + Duration::days(3) {
+        s.yellow().to_string()
+    } else if l < now + Duration::days(7) {
+        s.green().to_string()
+    } else {
+        s.bright_black().to_string()
+    }
+}
+
+/// `format_limit_color` padded to a fixed column width.
+fn format_limit_cell(limit: Option<db::Deadline>, zone: utils::DisplayZone) -> String {
+    let width = limit.map_or(4, |d| d.format(zone, "%Y-%m-%d").width());
+    format!("{}{}", format_limit_color(limit, zone), " ".repeat(16usize.saturating_sub(width)))
+}
+
 fn pad_title(title: &str, width: usize) -> String {
     let title_width = title.width();
     if title_width >= width {
@@ -262,25 +799,379 @@ fn pad_title(title: &str, width: usize) -> String {
     }
 }
 
-fn print_tasks(tasks: &[db::Task], show_all: bool) {
+fn print_tasks(conn: &rusqlite::Connection, tasks: &[db::Task], show_all: bool, zone: utils::DisplayZone) {
+    let metas = db::get_tag_metas(conn).unwrap();
     if show_all {
-        println!("  st  {}  {}", pad_title("title", 25), "limit");
-        println!("--------------------------------------------------");
+        println!("  st  pri {}  {}  tags", pad_title("title", 25), pad_title("limit", 16));
+        println!("----------------------------------------------------------------------");
         for t in tasks {
             let status = if t.is_done { "v ".green() } else { "- ".red() };
-            let limit = format_limit_color(t.limit);
-            println!("  {} {}  {}", status, pad_title(&t.title, 25), limit);
+            let limit = format_limit_cell(t.limit, zone);
+            println!("  {} {} {}  {}  {}", status, t.priority.to_symbol().red(), pad_title(&t.title, 25), limit, format_tags(&metas, &t.tags));
         }
     } else {
-        println!("  {}  {}", pad_title("title", 25), "limit");
-        println!("--------------------------------------------------");
+        println!("  pri {}  {}  tags", pad_title("title", 25), pad_title("limit", 16));
+        println!("----------------------------------------------------------------------");
         for t in tasks.iter().filter(|t| !t.is_done) {
-            let limit = format_limit_color(t.limit);
-            println!("  {}  {}", pad_title(&t.title, 25), limit);
+            let limit = format_limit_cell(t.limit, zone);
+            println!("  {} {}  {}  {}", t.priority.to_symbol().red(), pad_title(&t.title, 25), limit, format_tags(&metas, &t.tags));
         }
     }
 }
 
+fn print_details(conn: &rusqlite::Connection, task: &db::Task, zone: utils::DisplayZone) {
+    let metas = db::get_tag_metas(conn).unwrap();
+    println!("\n{}", "--- Task Details ---".cyan().bold());
+    println!("{}: {}", "ID".bold(), task.id);
+    println!("{}: {:?}", "Priority".bold(), task.priority);
+    println!("{}: {}", "Tags".bold(), format_tags(&metas, &task.tags));
+    println!("{}: {}", "Title".bold(), task.title);
+    println!("{}: {}", "Done".bold(), if task.is_done { "Yes".green() } else { "No".red() });
+    println!("{}: {}", "State".bold(), db::workflow(conn).unwrap().state_name(task));
+    let limit_zone = task.limit.and_then(|l| l.zone_label()).map(|z| format!(" ({})", z)).unwrap_or_default();
+    println!("{}: {}{}", "Limit".bold(), format_limit_color(task.limit, zone), limit_zone);
+    println!("{}: {}", "Scheduled".bold(), format_day(task.scheduled));
+    println!("{}: {}", "Wait until".bold(), format_day(task.wait_until));
+    if !task.dependencies.is_empty() {
+        let deps: Vec<String> = task.dependencies.iter()
+            .map(|&id| match db::get_task(conn, id).unwrap() {
+                Some(dep) if dep.is_done => format!("{} {}", id, dep.title).bright_black().to_string(),
+                Some(dep) => format!("{} {}", id, dep.title),
+                None => id.to_string(),
+            })
+            .collect();
+        println!("{}: {}", "Depends on".bold(), deps.join(", "));
+    }
+    if let Some(created) = task.created {
+        println!("{}: {}", "Created".bold(), zone.naive(created).format("%Y-%m-%d %H:%M"));
+    }
+    if let Some(estimate) = task.estimate {
+        println!("{}: {}", "Estimate".bold(), utils::format_duration(estimate));
+    }
+    let tracked = tracked_time(conn, task.id, Utc::now());
+    if tracked > Duration::zero() {
+        println!("{}: {}", "Tracked".bold(), utils::format_duration(tracked));
+    }
+    if let Some(count) = db::pomodoro_counts(conn).unwrap().get(&task.id) {
+        println!("{}: {}", "Pomodoros".bold(), count);
+    }
+    println!("{}: {}", "Description".bold(), task.description.clone().unwrap_or_else(|| "None".to_string()));
+}
+
+/// The tasks planned for `day` with their estimates, and whether they fit in
+/// the day's capacity.
+fn print_plan(conn: &rusqlite::Connection, day: NaiveDate, tasks: &[db::Task], capacity: Duration, zone: utils::DisplayZone) {
+    println!("{} {}", "Plan for".cyan().bold(), day.format("%Y-%m-%d (%a)"));
+    if tasks.is_empty() {
+        println!("Nothing due or scheduled.");
+        return;
+    }
+    let metas = db::get_tag_metas(conn).unwrap();
+    for t in tasks {
+        let estimate = t.estimate.map_or("?".bright_black().to_string(), utils::format_duration);
+        println!("  {:>7}  {}  {}  {}", estimate, pad_title(&t.title, 25), format_limit_cell(t.limit, zone), format_tags(&metas, &t.tags));
+    }
+
+    let total: Duration = tasks.iter().filter_map(|t| t.estimate).sum();
+    let unknown = tasks.iter().filter(|t| t.estimate.is_none()).count();
+    println!("----------------------------------------------------------------------");
+    let summary = format!("Total {} of {} capacity", utils::format_duration(total), utils::format_duration(capacity));
+    if total > capacity {
+        println!("{}", format!("{} - over by {}", summary, utils::format_duration(total - capacity)).red().bold());
+    } else {
+        println!("{} - {} free", summary.green(), utils::format_duration(capacity - total));
+    }
+    if unknown > 0 {
+        println!("{}", format!("{} task(s) without an estimate", unknown).yellow());
+    }
+}
+
+/// A bar of `width` cells for `value` out of `max`.
+fn text_bar(value: usize, max: usize, width: usize) -> String {
+    "█".repeat((value * width).div_ceil(max.max(1)))
+}
+
+fn print_stats(stats: &stats::Stats) {
+    println!("{}", "Completed per day".cyan().bold());
+    let max = stats.daily.iter().map(|(_, n)| *n).max().unwrap_or(0);
+    for (day, n) in &stats.daily {
+        println!("  {}  {:<30} {}", day.format("%m/%d %a"), text_bar(*n, max, 30).green(), n);
+    }
+
+    println!("\n{}", "Completed per week".cyan().bold());
+    let max = stats.weekly.iter().map(|(_, n)| *n).max().unwrap_or(0);
+    for (week, n) in &stats.weekly {
+        println!("  W{:02} {}  {:<30} {}", week.iso_week().week(), week.format("%m/%d"), text_bar(*n, max, 30).green(), n);
+    }
+
+    println!("\n{}", "Overview".cyan().bold());
+    let overdue = format!("{} overdue", stats.overdue);
+    println!("  {} open ({}), {} done", stats.open, if stats.overdue > 0 { overdue.red() } else { overdue.normal() }, stats.done);
+    let lead_time = stats.lead_time.map_or("-".to_string(), stats::format_lead_time);
+    println!("  Average lead time: {}", lead_time);
+    println!("  Streak: {} day(s) (best {})", stats.streak, stats.best_streak);
+
+    if !stats.tags.is_empty() {
+        println!("\n{}", "Completion by tag".cyan().bold());
+        let width = stats.tags.iter().map(|r| r.tag.width()).max().unwrap_or(0);
+        for rate in &stats.tags {
+            let filled = rate.done * 20 / rate.total;
+            println!(
+                "  #{}{}  {}{}  {:>3}%  {}/{}",
+                rate.tag,
+                " ".repeat(width - rate.tag.width()),
+                "█".repeat(filled).green(),
+                "░".repeat(20 - filled).bright_black(),
+                rate.percent(),
+                rate.done,
+                rate.total
+            );
+        }
+    }
+}
+
+fn print_burndown(points: &[stats::BurndownPoint]) {
+    let width = if points.len() <= 30 { 2 } else { 1 };
+    for line in stats::burndown_chart(points, 10, width) {
+        let colored: String = line.chars().map(|c| match c {
+            '█' => c.to_string().yellow().to_string(),
+            '░' => c.to_string().green().to_string(),
+            '·' => c.to_string().bright_black().to_string(),
+            _ => c.to_string(),
+        }).collect();
+        println!("{}", colored);
+    }
+    println!("      {} open  {} completed  {} ideal", "█".yellow(), "░".green(), "·".bright_black());
+    if let Some(last) = points.last() {
+        let total = last.open + last.completed;
+        println!("{} open, {} of {} completed ({}%)", last.open, last.completed, total, last.completed * 100 / total.max(1));
+    }
+}
+
+/// `rows` as a table with a total line.
+fn print_report(by: report::Group, rows: &[report::Row]) {
+    if rows.is_empty() {
+        println!("Nothing tracked or completed in this period.");
+        return;
+    }
+    let mut builder = Builder::default();
+    builder.push_record([format!("{:?}", by), "Time".into(), "Hours".into(), "Done".into()]);
+    for row in rows {
+        builder.push_record([row.label.clone(), utils::format_duration(row.tracked), report::hours(row.tracked), row.completed.to_string()]);
+    }
+    // Tags overlap, so a tag report has no meaningful total
+    if by != report::Group::Tag {
+        let tracked = rows.iter().map(|r| r.tracked).sum();
+        let completed: usize = rows.iter().map(|r| r.completed).sum();
+        builder.push_record(["Total".into(), utils::format_duration(tracked), report::hours(tracked), completed.to_string()]);
+    }
+    let mut table = builder.build();
+    table.with(Style::rounded()).with(Modify::new(Columns::new(1..)).with(Alignment::right()));
+    println!("{}", table);
+}
+
+fn print_stopped(conn: &rusqlite::Connection, entry: &db::TimeEntry, now: DateTime<Utc>) {
+    let title = db::get_task(conn, entry.task_id).unwrap().map(|t| t.title).unwrap_or_default();
+    println!(
+        "Stopped {} {} after {} (total {}).",
+        entry.task_id,
+        title.bold(),
+        utils::format_duration(entry.duration(now)).cyan(),
+        utils::format_duration(tracked_time(conn, entry.task_id, now)),
+    );
+}
+
+/// Everything tracked on a task, including a running timer.
+fn tracked_time(conn: &rusqlite::Connection, task_id: i64, now: DateTime<Utc>) -> Duration {
+    db::get_time_entries(conn, task_id).unwrap().iter().map(|e| e.duration(now)).sum()
+}
+
+/// Score factors as "+30 priority  high priority" lines.
+fn print_factors(factors: &[score::Factor]) {
+    for f in factors {
+        let points = format!("{:+4}", f.points);
+        let points = if f.points < 0 { points.red() } else { points.green() };
+        println!("  {} {:<12} {}", points, f.name, f.reason);
+    }
+}
+
+fn format_day(day: Option<chrono::NaiveDate>) -> String {
+    match day {
+        Some(d) => d.format("%Y-%m-%d").to_string(),
+        None => "None".bright_black().to_string(),
+    }
+}
+
+/// Open tasks grouped under Overdue, Today, Tomorrow and weekday headings,
+/// colored like limits are.
+fn print_agenda(conn: &rusqlite::Connection, tasks: &[db::Task], days: u32, zone: utils::DisplayZone) {
+    let metas = db::get_tag_metas(conn).unwrap();
+    let now = Utc::now();
+    let today = zone.today();
+
+    // `None` (overdue) sorts before every day
+    let mut groups: std::collections::BTreeMap<Option<NaiveDate>, Vec<&db::Task>> = std::collections::BTreeMap::new();
+    for task in tasks {
+        if let Some(limit) = task.limit {
+            let day = if limit.instant(zone) < now { None } else { Some(limit.date(zone)) };
+            groups.entry(day).or_default().push(task);
+        }
+    }
+    if groups.is_empty() {
+        println!("Nothing due in the next {} day(s).", days);
+        return;
+    }
+
+    for (day, tasks) in groups {
+        let heading = match day {
+            None => "Overdue".magenta().bold().to_string(),
+            Some(d) => {
+                let name = match (d - today).num_days() {
+                    0 => "Today".to_string(),
+                    1 => "Tomorrow".to_string(),
+                    _ => d.format("%A").to_string(),
+                };
+                let end_of_day = zone.resolve(d.and_hms_opt(23, 59, 59).unwrap());
+                paint_urgency(&format!("{} {}", name, d.format("%m/%d")), end_of_day).bold().to_string()
+            }
+        };
+        println!("{}", heading);
+        for t in tasks {
+            let limit = t.limit.unwrap();
+            let when = match (day, limit) {
+                (None, _) => limit.format(zone, "%m/%d"),
+                (_, db::Deadline::Day(_)) => "all day".to_string(),
+                _ => limit.local(zone).format("%H:%M").to_string(),
+            };
+            println!("  {} {:<11} {}  {}", t.priority.to_symbol().red(), when, pad_title(&t.title, 25), format_tags(&metas, &t.tags));
+        }
+    }
+}
+
+/// A month grid, Monday first, with the number of tasks due under each day.
+fn print_calendar(first: NaiveDate, counts: &HashMap<NaiveDate, usize>, today: NaiveDate) {
+    const CELL: usize = 6;
+    println!("{}", format!("{:^width$}", first.format("%B %Y").to_string(), width = CELL * 7).bold());
+    println!("{}", ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"].map(|d| format!(" {:<5}", d)).concat().bright_black());
+
+    let last = utils::month_days(first).last().unwrap();
+    let mut week = utils::week_start(first);
+    while week <= last {
+        let days: Vec<NaiveDate> = week.iter_days().take(7).collect();
+        let mut day_line = String::new();
+        let mut count_line = String::new();
+        for d in days {
+            if d.month() != first.month() {
+                day_line.push_str(&" ".repeat(CELL));
+                count_line.push_str(&" ".repeat(CELL));
+                continue;
+            }
+            let number = format!("{:>3}", d.day());
+            day_line.push_str(&if d == today { number.reversed().to_string() } else { number });
+            day_line.push_str(&" ".repeat(CELL - 3));
+
+            let count = counts.get(&d).copied().unwrap_or(0);
+            let label = if count > 0 { format!("{:>3}", format!("({})", count)) } else { String::new() };
+            let painted = match count {
+                0 => String::new(),
+                1 => label.green().to_string(),
+                2 | 3 => label.yellow().to_string(),
+                _ => label.red().bold().to_string(),
+            };
+            count_line.push_str(&painted);
+            count_line.push_str(&" ".repeat(CELL.saturating_sub(label.len())));
+        }
+        println!("{}", day_line.trim_end());
+        println!("{}", count_line.trim_end());
+        week += Duration::days(7);
+    }
+
+    let total: usize = counts.values().sum();
+    println!("{} open task(s) due this month.", total);
+}
+
+/// Shows how a quick-add line was understood.
+fn print_quick_add_preview(conn: &rusqlite::Connection, task: &db::Task, zone: utils::DisplayZone) {
+    let metas = db::get_tag_metas(conn).unwrap();
+    println!("  {:<9} {}", "Title:".bold(), task.title);
+    println!("  {:<9} {}", "Limit:".bold(), format_limit_color(task.limit, zone));
+    println!("  {:<9} {}", "Tags:".bold(), if task.tags.is_empty() { "None".bright_black().to_string() } else { format_tags(&metas, &task.tags) });
+    println!("  {:<9} {:?}", "Priority:".bold(), task.priority);
+    if let Some(estimate) = task.estimate {
+        println!("  {:<9} {}", "Estimate:".bold(), utils::format_duration(estimate));
+    }
+}
+
+/// A tag color (see `utils::tag_color`) as a terminal color.
+fn tag_color(color: utils::TagColor) -> Color {
+    match color {
+        utils::TagColor::Rgb(r, g, b) => Color::TrueColor { r, g, b },
+        utils::TagColor::Ansi(0) => Color::Black,
+        utils::TagColor::Ansi(1) => Color::Red,
+        utils::TagColor::Ansi(2) => Color::Green,
+        utils::TagColor::Ansi(3) => Color::Yellow,
+        utils::TagColor::Ansi(4) => Color::Blue,
+        utils::TagColor::Ansi(5) => Color::Magenta,
+        utils::TagColor::Ansi(6) => Color::Cyan,
+        utils::TagColor::Ansi(7) => Color::White,
+        utils::TagColor::Ansi(_) => Color::BrightBlack,
+    }
+}
+
+/// The tag as shown to the user (`icon #tag`), with its configured color.
+fn tag_label(metas: &HashMap<String, db::TagMeta>, tag: &str) -> (String, Color) {
+    let meta = db::find_tag_meta(metas, tag);
+    let icon = meta.and_then(|m| m.icon.as_deref()).map(|i| format!("{} ", i)).unwrap_or_default();
+    let color = tag_color(utils::tag_color(meta.and_then(|m| m.color.as_deref())));
+    (format!("{}#{}", icon, tag), color)
+}
+
+fn paint_tag(text: &str, color: Color) -> String {
+    text.color(color).to_string()
+}
+
+fn format_tag(metas: &HashMap<String, db::TagMeta>, tag: &str) -> String {
+    let (label, color) = tag_label(metas, tag);
+    paint_tag(&label, color)
+}
+
+fn format_tags(metas: &HashMap<String, db::TagMeta>, tags: &[String]) -> String {
+    tags.iter().map(|t| format_tag(metas, t)).collect::<Vec<_>>().join(" ")
+}
+
+fn print_tags(conn: &rusqlite::Connection, counts: &[(String, usize)]) {
+    if counts.is_empty() {
+        println!("No tags yet.");
+        return;
+    }
+    let metas = db::get_tag_metas(conn).unwrap();
+    println!("  {}  tasks  pri  description", pad_title("tag", 25));
+    println!("----------------------------------------------------------------------");
+    for (tag, count) in counts {
+        let meta = metas.get(tag);
+        let priority = meta.map_or(db::Priority::None, |m| m.default_priority);
+        let description = meta.and_then(|m| m.description.clone()).unwrap_or_default();
+        // Pad before coloring so escape codes don't break the alignment
+        let (label, color) = tag_label(&metas, tag);
+        println!("  {}  {:>5}  {}  {}", paint_tag(&pad_title(&label, 25), color), count, priority.to_symbol().red(), description);
+    }
+}
+
+/// Applies `change` to the metadata of `tag`, creating it if needed.
+fn update_tag_meta(conn: &rusqlite::Connection, tag: &str, change: impl FnOnce(&mut db::TagMeta)) {
+    let mut meta = db::get_tag_meta(conn, tag).unwrap().unwrap_or_else(|| db::TagMeta {
+        name: tag.to_string(),
+        ..Default::default()
+    });
+    change(&mut meta);
+    db::set_tag_meta(conn, &meta).unwrap();
+}
+
+/// `"none"` (or an empty value) clears an optional tag setting.
+fn optional_setting(value: String) -> Option<String> {
+    if value.is_empty() || value.eq_ignore_ascii_case("none") { None } else { Some(value) }
+}
+
 fn resolve_id(conn: &rusqlite::Connection, id: Option<i64>) -> Option<i64> {
     if let Some(id) = id {
         if db::get_task(conn, id).unwrap().is_some() {
@@ -1,5 +1,7 @@
 use clap::{Parser, Subcommand};
 
+use crate::report::Group;
+
 #[derive(Parser)]
 #[command(name = "sakd")]
 #[command(about = "Fastest, most useful CLI task manager", long_about = None)]
@@ -13,17 +15,35 @@ pub enum Commands {
     /// Add a new task
     #[command(alias = "a")]
     Add {
-        /// Task title
+        /// Task in one line, e.g. "Send report tomorrow 15:00 #work !high" (prompts when omitted)
         title: Option<String>,
-        /// Task limit date (e.g. 2026-01-16T20:00:00)
+        /// Task limit as RFC 3339 (e.g. 2026-01-16T20:00:00+09:00)
         #[arg(short, long)]
         limit: Option<String>,
+        /// Zone the limit's time is in: an IANA name (Asia/Tokyo), floating or local
+        #[arg(long)]
+        tz: Option<String>,
+        /// Day you plan to work on the task (e.g. mon, 2026/03/01)
+        #[arg(short, long)]
+        scheduled: Option<String>,
+        /// Hide the task until this day
+        #[arg(short, long)]
+        wait: Option<String>,
         /// Task description
         #[arg(short, long)]
         description: Option<String>,
+        /// Task priority (high, medium, low)
+        #[arg(short, long)]
+        priority: Option<String>,
         /// Task tags (comma separated)
         #[arg(long)]
         tags: Option<String>,
+        /// IDs of tasks to finish first (comma separated)
+        #[arg(long)]
+        dep: Option<String>,
+        /// Expected effort, e.g. 30m, 2h, 1h30m, 1d (a working day)
+        #[arg(long)]
+        estimate: Option<String>,
     },
     /// Mark a task as done
     #[command(alias = "d")]
@@ -31,21 +51,95 @@ pub enum Commands {
         /// Task ID
         id: Option<i64>,
     },
+    /// Start tracking time on a task (stops the running timer)
+    Start {
+        /// Task ID
+        id: Option<i64>,
+    },
+    /// Stop the running timer
+    Stop,
+    /// Show the running timer
+    Status,
+    /// Summarize tracked time and completed tasks over a period
+    Report {
+        /// First day (default: the start of this week)
+        #[arg(long)]
+        from: Option<String>,
+        /// Last day (default: today)
+        #[arg(long)]
+        to: Option<String>,
+        /// Group rows by tag, day or task
+        #[arg(long, value_enum, default_value_t = Group::Tag)]
+        by: Group,
+        /// Print CSV instead of a table
+        #[arg(long)]
+        csv: bool,
+    },
+    /// Show completion statistics: per day and week, lead time, tags, streaks
+    Stats {
+        /// Number of days in the daily chart (1-3660)
+        #[arg(long, default_value_t = 14, value_parser = clap::value_parser!(u32).range(1..=3660))]
+        days: u32,
+    },
+    /// Chart open vs completed tasks of a tag over time
+    Burndown {
+        /// Tag of the sprint or project
+        #[arg(long)]
+        tag: String,
+        /// First day (default: when the first of its tasks was created, at most 30 days before the last)
+        #[arg(long)]
+        from: Option<String>,
+        /// Last day (default: today)
+        #[arg(long)]
+        to: Option<String>,
+    },
     /// Remove a task
     #[command(alias = "r")]
     Remove {
         /// Task ID
         id: Option<i64>,
     },
+    /// Move a task in the manual order (`list --sort manual`)
+    Move {
+        /// Task ID
+        id: i64,
+        /// Put it right before this task
+        #[arg(long, conflicts_with = "after", required_unless_present = "after")]
+        before: Option<i64>,
+        /// Put it right after this task
+        #[arg(long)]
+        after: Option<i64>,
+    },
     /// List all tasks
     #[command(alias = "l")]
     List {
         /// Show all tasks including completed ones
         #[arg(short, long)]
         all: bool,
-        /// Filter by tag
+        /// Show only completed tasks
+        #[arg(long, conflicts_with = "all")]
+        done: bool,
+        /// Include tasks that are waiting (hidden until their wait date)
+        #[arg(long)]
+        waiting: bool,
+        /// Filter by tag, including its sub-tags (`work` matches `work/api`)
         #[arg(long)]
         tag: Option<String>,
+        /// Only tasks due on or before this date (e.g. 2026/03/01, tm, 1w)
+        #[arg(long)]
+        before: Option<String>,
+        /// Only tasks due on or after this date
+        #[arg(long)]
+        after: Option<String>,
+        /// Sort keys, e.g. limit,-created,title (limit, priority, created, scheduled, title, id, manual; - reverses)
+        #[arg(long, allow_hyphen_values = true)]
+        sort: Option<String>,
+        /// Maximum number of tasks to show
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+        /// Number of tasks to skip
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
     },
     /// Show task details
     #[command(alias = "s")]
@@ -58,5 +152,142 @@ pub enum Commands {
     Edit {
         /// Task ID
         id: Option<i64>,
+        /// Read the limit's time in another zone (IANA name, floating or local) without prompting
+        #[arg(long)]
+        tz: Option<String>,
+        /// Set the scheduled day without prompting ("none" to clear)
+        #[arg(short, long)]
+        scheduled: Option<String>,
+        /// Set the wait date without prompting ("none" to clear)
+        #[arg(short, long)]
+        wait: Option<String>,
+        /// Move the task to a workflow state, e.g. in-progress (the last one completes it)
+        #[arg(long)]
+        state: Option<String>,
+        /// IDs of tasks to finish first, comma separated ("none" to clear)
+        #[arg(long)]
+        dep: Option<String>,
+        /// Set the expected effort without prompting ("none" to clear)
+        #[arg(long)]
+        estimate: Option<String>,
+    },
+    /// Sum up the estimates of what is due or scheduled on a day
+    Plan {
+        /// Day to plan (default: today)
+        day: Option<String>,
+    },
+    /// Suggest what to work on now, and why
+    Next {
+        /// Number of suggestions
+        #[arg(short = 'n', long, default_value_t = 1)]
+        count: usize,
+        /// Only tasks with this tag or its sub-tags
+        #[arg(long)]
+        tag: Option<String>,
+    },
+    /// Show today's plan (scheduled tasks) and what is due today
+    Today,
+    /// Show open tasks grouped by the day they are due
+    Agenda {
+        /// Number of days to show, starting today (1-3660)
+        #[arg(long, default_value_t = 7, value_parser = clap::value_parser!(u32).range(1..=3660))]
+        days: u32,
+    },
+    /// Show a month calendar with the number of tasks due each day
+    Cal {
+        /// Month, e.g. 2026-03, 3 or mar (the current month when omitted)
+        month: Option<String>,
+    },
+    /// List tags with task counts, or manage them
+    #[command(alias = "t")]
+    Tags {
+        #[command(subcommand)]
+        action: Option<TagCommands>,
+    },
+    /// Export tasks as an iCalendar (.ics) file of to-dos
+    Export {
+        /// Include completed tasks
+        #[arg(short, long)]
+        all: bool,
+        /// File to write (prints to stdout when omitted)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Show settings, or change them
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigCommands>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TagCommands {
+    /// Rename a tag on all tasks
+    Rename {
+        /// Current tag name
+        old: String,
+        /// New tag name
+        new: String,
+    },
+    /// Merge a tag into another one
+    Merge {
+        /// Tag to merge (will disappear)
+        from: String,
+        /// Tag to keep
+        into: String,
+    },
+    /// Remove a tag from all tasks
+    Delete {
+        /// Tag name
+        tag: String,
+    },
+    /// Set the color a tag is shown in (black, red, green, yellow, blue, magenta, cyan, white, gray or #rrggbb)
+    SetColor {
+        /// Tag name
+        tag: String,
+        /// Color name, or "none" to reset
+        color: String,
+    },
+    /// Set an icon shown before a tag
+    SetIcon {
+        /// Tag name
+        tag: String,
+        /// Icon (any short text, e.g. an emoji), or "none" to reset
+        icon: String,
+    },
+    /// Describe what a tag is for
+    Describe {
+        /// Tag name
+        tag: String,
+        /// Description, or "none" to reset
+        description: String,
+    },
+    /// Set the priority given to new tasks with this tag
+    SetPriority {
+        /// Tag name
+        tag: String,
+        /// Priority (high, medium, low, none)
+        priority: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Print the value of a setting
+    Get {
+        /// Setting name
+        key: String,
+    },
+    /// Change a setting (e.g. `sakd config set timezone Asia/Tokyo`)
+    Set {
+        /// Setting name
+        key: String,
+        /// New value
+        value: String,
+    },
+    /// Reset a setting to its default
+    Unset {
+        /// Setting name
+        key: String,
     },
 }
@@ -1,16 +1,282 @@
-use rusqlite::{params, Connection, Result};
-use chrono::{DateTime, Utc};
+use rusqlite::types::{FromSql, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 
-#[derive(Clone)]
+use crate::pomodoro;
+use crate::utils::{self, DeadlineZone, DisplayZone, Workflow};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub enum Priority {
+    #[default]
+    None,
+    Low,
+    Medium,
+    High,
+}
+
+impl Priority {
+    pub fn to_symbol(&self) -> &'static str {
+        match self {
+            Priority::None => "   ",
+            Priority::Low => "!  ",
+            Priority::Medium => "!! ",
+            Priority::High => "!!!",
+        }
+    }
+}
+
+impl ToSql for Priority {
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(*self as i64))
+    }
+}
+
+impl FromSql for Priority {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        Ok(match i64::column_result(value)? {
+            1 => Priority::Low,
+            2 => Priority::Medium,
+            3 => Priority::High,
+            _ => Priority::None,
+        })
+    }
+}
+
+/// When a task is due.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Deadline {
+    /// A fixed instant, with the zone it was entered in if one was given.
+    At(DateTime<Utc>, Option<Tz>),
+    /// A wall time in whatever the display zone is, so it doesn't shift when
+    /// traveling.
+    Floating(NaiveDateTime),
+    /// Due some time on this day, in whatever the display zone is. Sorts after
+    /// the timed deadlines of the same day.
+    Day(NaiveDate),
+}
+
+impl Deadline {
+    /// The instant the deadline falls due, floating ones read in `display`.
+    /// All-day deadlines fall due at the last second of their day.
+    pub fn instant(&self, display: DisplayZone) -> DateTime<Utc> {
+        display.resolve(match self {
+            Deadline::At(at, _) => return *at,
+            Deadline::Floating(naive) => *naive,
+            Deadline::Day(date) => date.and_hms_opt(23, 59, 59).unwrap(),
+        })
+    }
+
+    /// The wall time in the display zone (the end of the day for all-day deadlines).
+    pub fn local(&self, display: DisplayZone) -> NaiveDateTime {
+        match self {
+            Deadline::At(at, _) => display.naive(*at),
+            Deadline::Floating(naive) => *naive,
+            Deadline::Day(date) => date.and_hms_opt(23, 59, 59).unwrap(),
+        }
+    }
+
+    /// The day the deadline falls on in the display zone.
+    pub fn date(&self, display: DisplayZone) -> NaiveDate {
+        self.local(display).date()
+    }
+
+    /// The deadline in the display zone, as `date_format` followed by `HH:MM`
+    /// unless it is all-day.
+    pub fn format(&self, display: DisplayZone, date_format: &str) -> String {
+        match self {
+            Deadline::Day(date) => date.format(date_format).to_string(),
+            _ => self.local(display).format(&format!("{} %H:%M", date_format)).to_string(),
+        }
+    }
+
+    /// The wall time in the deadline's own zone, which is what editing starts from.
+    pub fn own_local(&self, display: DisplayZone) -> NaiveDateTime {
+        match self {
+            Deadline::At(at, Some(tz)) => at.with_timezone(tz).naive_local(),
+            _ => self.local(display),
+        }
+    }
+
+    /// The zone new wall times for this deadline are read in.
+    pub fn zone(&self) -> DeadlineZone {
+        match self {
+            Deadline::At(_, Some(tz)) => DeadlineZone::Named(*tz),
+            Deadline::At(_, None) => DeadlineZone::Local,
+            Deadline::Floating(_) | Deadline::Day(_) => DeadlineZone::Floating,
+        }
+    }
+
+    /// How the deadline relates to zones, for display: `floating`, or the zone
+    /// it was entered in with its wall time there.
+    pub fn zone_label(&self) -> Option<String> {
+        match self {
+            Deadline::At(at, Some(tz)) => Some(format!("{} {}", at.with_timezone(tz).format("%H:%M"), tz.name())),
+            Deadline::At(_, None) | Deadline::Day(_) => None,
+            Deadline::Floating(_) => Some(FLOATING.to_string()),
+        }
+    }
+
+    /// The deadline moved to another day, keeping its time and zone.
+    pub fn moved_to(&self, date: NaiveDate, display: DisplayZone) -> Self {
+        match self {
+            Deadline::Day(_) => Deadline::Day(date),
+            _ => Deadline::Floating(date.and_time(self.own_local(display).time())).rezone(self.zone(), display),
+        }
+    }
+
+    /// The same wall time read in `zone` instead; see `utils::resolve_local` for
+    /// times that don't exist or repeat there. All-day deadlines have no time
+    /// and stay as they are.
+    pub fn rezone(&self, zone: DeadlineZone, display: DisplayZone) -> Self {
+        if let Deadline::Day(_) = self {
+            return *self;
+        }
+        let naive = self.own_local(display);
+        match zone {
+            DeadlineZone::Local => Deadline::At(display.resolve(naive), None),
+            DeadlineZone::Floating => Deadline::Floating(naive),
+            DeadlineZone::Named(tz) => Deadline::At(utils::resolve_local(&tz, naive), Some(tz)),
+        }
+    }
+}
+
+/// Values of `limit_tz` marking deadlines kept as wall time in `limit_local`.
+const FLOATING: &str = "floating";
+const ALL_DAY: &str = "date";
+const LOCAL_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// `limit_at`, `limit_tz` and `limit_local` for a deadline. Floating and all-day
+/// deadlines also get `limit_at`, resolved in `display`, so range filters and
+/// ordering work in SQL.
+fn deadline_columns(limit: Option<&Deadline>, display: DisplayZone) -> (Option<String>, Option<String>, Option<String>) {
+    let Some(d) = limit else {
+        return (None, None, None);
+    };
+    let at = Some(d.instant(display).to_rfc3339());
+    match d {
+        Deadline::At(_, tz) => (at, tz.map(|tz| tz.name().to_string()), None),
+        Deadline::Floating(naive) => (at, Some(FLOATING.to_string()), Some(naive.format(LOCAL_FORMAT).to_string())),
+        Deadline::Day(date) => (at, Some(ALL_DAY.to_string()), Some(date.format(DATE_FORMAT).to_string())),
+    }
+}
+
+/// A floating or all-day deadline from its `limit_tz` and `limit_local`.
+fn local_deadline(kind: &str, local: &str) -> Option<Deadline> {
+    match kind {
+        FLOATING => NaiveDateTime::parse_from_str(local, LOCAL_FORMAT).ok().map(Deadline::Floating),
+        ALL_DAY => NaiveDate::parse_from_str(local, DATE_FORMAT).ok().map(Deadline::Day),
+        _ => None,
+    }
+}
+
+fn date_to_sql(date: Option<NaiveDate>) -> Option<String> {
+    date.map(|d| d.format(DATE_FORMAT).to_string())
+}
+
+fn date_from_sql(value: Option<String>) -> Option<NaiveDate> {
+    value.and_then(|v| NaiveDate::parse_from_str(&v, DATE_FORMAT).ok())
+}
+
+fn read_deadline(row: &rusqlite::Row) -> Result<Option<Deadline>> {
+    let at: Option<String> = row.get("limit_at")?;
+    let tz: Option<String> = row.get("limit_tz")?;
+    let local: Option<String> = row.get("limit_local")?;
+
+    if let (Some(kind), Some(local)) = (&tz, &local)
+        && let Some(deadline) = local_deadline(kind, local) {
+        return Ok(Some(deadline));
+    }
+    let at = at.and_then(|s| DateTime::parse_from_rfc3339(&s).ok().map(|dt| dt.with_timezone(&Utc)));
+    Ok(at.map(|at| Deadline::At(at, tz.and_then(|tz| tz.parse().ok()))))
+}
+
+#[derive(Clone, PartialEq, Debug)]
 pub struct Task {
     pub id: i64,
     pub title: String,
     pub is_done: bool,
-    pub limit: Option<DateTime<Utc>>,
+    /// Workflow state between the first and the last one (see `utils::Workflow`);
+    /// `None` for tasks that are not started or done.
+    pub state: Option<String>,
+    pub limit: Option<Deadline>,
+    /// The day the task is planned to be worked on.
+    pub scheduled: Option<NaiveDate>,
+    /// The task is not actionable before this day and hidden from lists until then.
+    pub wait_until: Option<NaiveDate>,
     pub description: Option<String>,
+    pub priority: Priority,
     pub tags: Vec<String>,
+    /// Expected effort, see `utils::parse_duration`.
+    pub estimate: Option<chrono::Duration>,
+    /// Ids of the tasks that have to be done before this one.
+    pub dependencies: Vec<i64>,
+    /// When the task was added; unknown for tasks added by older versions.
+    pub created: Option<DateTime<Utc>>,
+    /// Place in the manual order, changed with `move_task`. New tasks go last.
+    pub position: i64,
+    /// When the task was marked done; set by `add_task`/`update_task`.
+    pub completed: Option<DateTime<Utc>>,
+}
+
+impl Task {
+    /// A new, not yet stored task (`id` is assigned by `add_task`).
+    pub fn new(title: &str) -> Self {
+        Task {
+            id: 0,
+            title: title.to_string(),
+            is_done: false,
+            state: None,
+            limit: None,
+            scheduled: None,
+            wait_until: None,
+            description: None,
+            priority: Priority::None,
+            tags: Vec::new(),
+            estimate: None,
+            dependencies: Vec::new(),
+            created: None,
+            position: 0,
+            completed: None,
+        }
+    }
+
+    /// Whether the task is still waiting on `today`.
+    pub fn is_waiting(&self, today: NaiveDate) -> bool {
+        self.wait_until.is_some_and(|w| w > today)
+    }
+}
+
+/// A stretch of time spent on a task; `stopped` is `None` while it runs.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TimeEntry {
+    pub id: i64,
+    pub task_id: i64,
+    pub started: DateTime<Utc>,
+    pub stopped: Option<DateTime<Utc>>,
+}
+
+impl TimeEntry {
+    /// Time spent so far, counting a running entry up to `now`.
+    pub fn duration(&self, now: DateTime<Utc>) -> chrono::Duration {
+        self.stopped.unwrap_or(now) - self.started
+    }
+}
+
+/// Presentation settings for a tag, stored in the `tags` table.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct TagMeta {
+    pub name: String,
+    pub color: Option<String>,
+    pub description: Option<String>,
+    pub icon: Option<String>,
+    /// Applied to new tasks carrying the tag when no priority is given.
+    pub default_priority: Priority,
 }
 
 fn get_db_path() -> PathBuf {
@@ -26,7 +292,23 @@ fn get_db_path() -> PathBuf {
 pub fn init_db() -> Result<Connection> {
     let db_path = get_db_path();
     let conn = Connection::open(db_path)?;
-    
+
+    // WAL lets the CLI write while a TUI session keeps reading; wait instead of
+    // failing when the other process holds the write lock.
+    conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+    conn.busy_timeout(std::time::Duration::from_secs(5))?;
+
+    init_schema(&conn)?;
+    // The system zone may have changed since the last run
+    refresh_floating_limits(&conn)?;
+    Ok(conn)
+}
+
+/// Creates the tables and indexes on an already opened connection.
+pub fn init_schema(conn: &Connection) -> Result<()> {
+    // Off by default in SQLite; needed for ON DELETE CASCADE on task_tags
+    conn.pragma_update(None, "foreign_keys", true)?;
+
     // Core tasks table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS tasks (
@@ -49,91 +331,564 @@ pub fn init_db() -> Result<Connection> {
         )",
         [],
     )?;
-    
-    Ok(conn)
-}
 
-pub fn add_task(
-    conn: &Connection, 
-    title: &str, 
-    limit: Option<DateTime<Utc>>, 
-    description: Option<String>,
-    tags: Vec<String>,
-) -> Result<i64> {
+    // Tasks that have to be done first
     conn.execute(
-        "INSERT INTO tasks (title, is_done, limit_at, description) VALUES (?, 0, ?, ?)",
+        "CREATE TABLE IF NOT EXISTS task_deps (
+            task_id INTEGER,
+            depends_on INTEGER,
+            PRIMARY KEY (task_id, depends_on),
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+            FOREIGN KEY (depends_on) REFERENCES tasks(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Time tracked on tasks; at most one entry is running (no `stopped_at`)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS time_entries (
+            id INTEGER PRIMARY KEY,
+            task_id INTEGER NOT NULL,
+            started_at TEXT NOT NULL,
+            stopped_at TEXT,
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_time_entries_task ON time_entries(task_id)", [])?;
+    // Older databases could end up with several running entries; each one
+    // stops when the next one started
+    conn.execute(
+        "UPDATE time_entries AS e SET stopped_at = (
+             SELECT MIN(n.started_at) FROM time_entries n WHERE n.stopped_at IS NULL AND n.id > e.id
+         )
+         WHERE stopped_at IS NULL AND id < (SELECT MAX(id) FROM time_entries WHERE stopped_at IS NULL)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_time_entries_running ON time_entries((stopped_at IS NULL))
+         WHERE stopped_at IS NULL",
+        [],
+    )?;
+
+    // Pomodoros finished in the TUI
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS pomodoros (
+            id INTEGER PRIMARY KEY,
+            task_id INTEGER NOT NULL,
+            completed_at TEXT NOT NULL,
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_pomodoros_task ON pomodoros(task_id)", [])?;
+
+    // Per-tag presentation (color, icon...) and defaults
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tags (
+            name TEXT PRIMARY KEY,
+            color TEXT,
+            description TEXT,
+            icon TEXT,
+            default_priority INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+
+    // Key-value settings changed with `sakd config`
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    add_column_if_missing(conn, "tasks", "priority", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "tasks", "limit_tz", "TEXT")?;
+    add_column_if_missing(conn, "tasks", "limit_local", "TEXT")?;
+    add_column_if_missing(conn, "tasks", "scheduled_on", "TEXT")?;
+    add_column_if_missing(conn, "tasks", "wait_until", "TEXT")?;
+    add_column_if_missing(conn, "tasks", "state", "TEXT")?;
+    add_column_if_missing(conn, "tasks", "created_at", "TEXT")?;
+    add_column_if_missing(conn, "tasks", "completed_at", "TEXT")?;
+    add_column_if_missing(conn, "tasks", "estimate_minutes", "INTEGER")?;
+    if add_column_if_missing(conn, "tasks", "position", "INTEGER NOT NULL DEFAULT 0")? {
+        // Start the manual order off in creation order
+        conn.execute("UPDATE tasks SET position = id", [])?;
+    }
+    // Databases that got a nullable column may hold rows without a place
+    conn.execute("UPDATE tasks SET position = id WHERE position IS NULL", [])?;
+
+    // Lookups by tag (filtering, tag listing) would otherwise scan the whole table
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_task_tags_tag ON task_tags(tag)", [])?;
+
+    // Tags left behind by deletions made before foreign keys were enforced
+    conn.execute("DELETE FROM task_tags WHERE task_id NOT IN (SELECT id FROM tasks)", [])?;
+
+    Ok(())
+}
+
+/// Adds a column to databases created by older versions, returning whether it
+/// had to be added.
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<bool> {
+    let exists: bool = conn.query_row(
+        &format!("SELECT COUNT(*) > 0 FROM pragma_table_info('{}') WHERE name = ?", table),
+        params![column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(!exists)
+}
+
+/// Records that `task_id` waits on each of `dependencies`. Dependencies that
+/// would make a cycle are rejected, and so are ids of missing tasks, by the
+/// foreign key.
+fn insert_dependencies(conn: &Connection, task_id: i64, dependencies: &[i64]) -> Result<()> {
+    if let Some(dep) = dependency_cycle(conn, task_id, dependencies)? {
+        let message = if dep == task_id {
+            format!("task {} can't depend on itself", task_id)
+        } else {
+            format!("task {} already depends on task {}", dep, task_id)
+        };
+        return Err(constraint_error(message));
+    }
+    for dep in dependencies {
+        conn.execute("INSERT OR IGNORE INTO task_deps (task_id, depends_on) VALUES (?, ?)", params![task_id, dep])?;
+    }
+    Ok(())
+}
+
+/// An error for writes that would break an invariant the schema can't express.
+fn constraint_error(message: String) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT), Some(message))
+}
+
+/// The first of `dependencies` that is `task_id` itself or already waits on
+/// it, directly or through other tasks, so that depending on it would make a
+/// cycle.
+pub fn dependency_cycle(conn: &Connection, task_id: i64, dependencies: &[i64]) -> Result<Option<i64>> {
+    let mut edges: HashMap<i64, Vec<i64>> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT task_id, depends_on FROM task_deps")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        edges.entry(row.get(0)?).or_default().push(row.get(1)?);
+    }
+
+    for &dep in dependencies {
+        let mut seen = HashSet::new();
+        let mut stack = vec![dep];
+        while let Some(id) = stack.pop() {
+            if id == task_id {
+                return Ok(Some(dep));
+            }
+            if seen.insert(id) {
+                stack.extend(edges.get(&id).into_iter().flatten());
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// For every task waiting on unfinished tasks, the ids of those tasks.
+pub fn open_dependencies(conn: &Connection) -> Result<HashMap<i64, Vec<i64>>> {
+    let mut stmt = conn.prepare(
+        "SELECT d.task_id, d.depends_on FROM task_deps d JOIN tasks t ON t.id = d.depends_on
+         WHERE t.is_done = 0 ORDER BY d.task_id, d.depends_on",
+    )?;
+    let mut open: HashMap<i64, Vec<i64>> = HashMap::new();
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        open.entry(row.get(0)?).or_default().push(row.get(1)?);
+    }
+    Ok(open)
+}
+
+/// The state to store: done tasks are past every intermediate state, so
+/// toggling them back to open starts the workflow over.
+fn open_state(task: &Task) -> Option<&str> {
+    task.state.as_deref().filter(|_| !task.is_done)
+}
+
+/// Stores a new task and returns its id. `task.id` is ignored. When the task has
+/// no priority, the highest default priority among its tags is used.
+pub fn add_task(conn: &Connection, task: &Task) -> Result<i64> {
+    let tx = conn.unchecked_transaction()?;
+
+    let mut priority = task.priority;
+    if priority == Priority::None {
+        let metas = get_tag_metas(&tx)?;
+        priority = task.tags.iter()
+            .filter_map(|tag| find_tag_meta(&metas, tag))
+            .map(|m| m.default_priority)
+            .max()
+            .unwrap_or_default();
+    }
+
+    let (limit_at, limit_tz, limit_local) = deadline_columns(task.limit.as_ref(), display_zone(&tx)?);
+    tx.execute(
+        "INSERT INTO tasks (title, is_done, state, limit_at, limit_tz, limit_local, scheduled_on, wait_until, description, priority,
+         estimate_minutes, created_at, completed_at, position)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, (SELECT COALESCE(MAX(position), 0) + 1 FROM tasks))",
         params![
-            title, 
-            limit.map(|t| t.to_rfc3339()), 
-            description,
+            task.title,
+            task.is_done,
+            open_state(task),
+            limit_at,
+            limit_tz,
+            limit_local,
+            date_to_sql(task.scheduled),
+            date_to_sql(task.wait_until),
+            task.description,
+            priority,
+            task.estimate.map(|e| e.num_minutes()),
+            task.created.unwrap_or_else(Utc::now).to_rfc3339(),
+            task.is_done.then(|| task.completed.unwrap_or_else(Utc::now).to_rfc3339()),
         ],
     )?;
-    let task_id = conn.last_insert_rowid();
+    let task_id = tx.last_insert_rowid();
 
-    for tag in tags {
-        conn.execute("INSERT INTO task_tags (task_id, tag) VALUES (?, ?)", params![task_id, tag])?;
+    for tag in &task.tags {
+        tx.execute("INSERT INTO task_tags (task_id, tag) VALUES (?, ?)", params![task_id, tag])?;
     }
+    insert_dependencies(&tx, task_id, &task.dependencies)?;
 
+    tx.commit()?;
     Ok(task_id)
 }
 
-pub fn get_tasks(conn: &Connection) -> Result<Vec<Task>> {
-    let mut stmt = conn.prepare("SELECT id, title, is_done, limit_at, description FROM tasks ORDER BY is_done ASC, limit_at IS NULL, limit_at ASC")?;
-    let task_rows = stmt.query_map([], |row| {
-        let task_id: i64 = row.get(0)?;
-        let limit_str: Option<String> = row.get(3)?;
-        let limit = limit_str.and_then(|s| DateTime::parse_from_rfc3339(&s).ok().map(|dt| dt.with_timezone(&Utc)));
-        
-        Ok((task_id, row.get(1)?, row.get(2)?, limit, row.get(4)?))
-    })?;
+const TASK_COLUMNS: &str = "t.id, t.title, t.is_done, t.state, t.limit_at, t.limit_tz, t.limit_local, t.scheduled_on, t.wait_until,
+    t.description, t.priority, t.estimate_minutes, t.created_at, t.completed_at, t.position, tt.tag,
+    (SELECT group_concat(d.depends_on) FROM task_deps d WHERE d.task_id = t.id) AS deps";
+
+/// Loads tasks and their tags with a single joined query.
+///
+/// Rows are expected to be grouped by task id; consecutive rows of the same
+/// task are folded into one `Task` carrying all of its tags.
+fn load_tasks<P: rusqlite::Params>(conn: &Connection, sql: &str, params: P) -> Result<Vec<Task>> {
+    let mut stmt = conn.prepare(sql)?;
+    let mut rows = stmt.query(params)?;
 
-    let mut tasks = Vec::new();
-    for r in task_rows {
-        let (id, title, is_done, limit, description) = r?;
-        
-        // Tags
-        let mut tag_stmt = conn.prepare("SELECT tag FROM task_tags WHERE task_id = ?")?;
-        let tags = tag_stmt.query_map(params![id], |r| r.get(0))?.collect::<Result<Vec<String>, _>>()?;
+    let mut tasks: Vec<Task> = Vec::new();
+    while let Some(row) = rows.next()? {
+        let task_id: i64 = row.get("id")?;
+        let tag: Option<String> = row.get("tag")?;
+
+        if let Some(last) = tasks.last_mut().filter(|t| t.id == task_id) {
+            last.tags.extend(tag);
+            continue;
+        }
 
         tasks.push(Task {
-            id,
-            title,
-            is_done,
-            limit,
-            description,
-            tags,
+            id: task_id,
+            title: row.get("title")?,
+            is_done: row.get("is_done")?,
+            state: row.get("state")?,
+            limit: read_deadline(row)?,
+            scheduled: date_from_sql(row.get("scheduled_on")?),
+            wait_until: date_from_sql(row.get("wait_until")?),
+            description: row.get("description")?,
+            priority: row.get("priority")?,
+            tags: tag.into_iter().collect(),
+            estimate: row.get::<_, Option<i64>>("estimate_minutes")?.map(chrono::Duration::minutes),
+            dependencies: row.get::<_, Option<String>>("deps")?
+                .map(|deps| deps.split(',').filter_map(|id| id.parse().ok()).collect())
+                .unwrap_or_default(),
+            created: row.get::<_, Option<String>>("created_at")?.map(|s| time_from_sql(&s)),
+            completed: row.get::<_, Option<String>>("completed_at")?.map(|s| time_from_sql(&s)),
+            position: row.get("position")?,
         });
     }
     Ok(tasks)
 }
 
+/// Filters, ordering and paging for loading tasks, evaluated in SQL.
+#[derive(Clone, Default)]
+pub struct TaskQuery {
+    tag: Option<String>,
+    done: Option<bool>,
+    due_before: Option<DateTime<Utc>>,
+    due_after: Option<DateTime<Utc>>,
+    actionable_on: Option<NaiveDate>,
+    scheduled_by: Option<NaiveDate>,
+    completed_between: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    order: Vec<SortKey>,
+    limit: Option<usize>,
+    offset: usize,
+}
+
+impl TaskQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only tasks tagged `tag` or one of its descendants (`work` also matches
+    /// `work/api`), case insensitive.
+    pub fn tag(mut self, tag: Option<String>) -> Self {
+        self.tag = tag.map(|t| crate::utils::normalize_tag(&t)).filter(|t| !t.is_empty());
+        self
+    }
+
+    /// `Some(false)` for open tasks, `Some(true)` for completed ones, `None` for both.
+    pub fn done(mut self, done: Option<bool>) -> Self {
+        self.done = done;
+        self
+    }
+
+    pub fn due_before(mut self, limit: Option<DateTime<Utc>>) -> Self {
+        self.due_before = limit;
+        self
+    }
+
+    pub fn due_after(mut self, limit: Option<DateTime<Utc>>) -> Self {
+        self.due_after = limit;
+        self
+    }
+
+    /// Only tasks completed at or after the first instant and before the second.
+    pub fn completed_between(mut self, range: Option<(DateTime<Utc>, DateTime<Utc>)>) -> Self {
+        self.completed_between = range;
+        self
+    }
+
+    /// Hides tasks still waiting on `date` (see `Task::wait_until`).
+    pub fn actionable_on(mut self, date: Option<NaiveDate>) -> Self {
+        self.actionable_on = date;
+        self
+    }
+
+    /// Only tasks scheduled on or before `date`.
+    pub fn scheduled_by(mut self, date: Option<NaiveDate>) -> Self {
+        self.scheduled_by = date;
+        self
+    }
+
+    pub fn limit(mut self, limit: Option<usize>) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Orders open tasks by `keys`, then by id; completed tasks always come
+    /// last. Without keys, tasks are ordered by limit.
+    pub fn order(mut self, keys: Vec<SortKey>) -> Self {
+        self.order = keys;
+        self
+    }
+
+    fn order_sql(&self) -> String {
+        let default = [SortKey { field: SortField::Limit, descending: false }];
+        let keys = if self.order.is_empty() { &default[..] } else { &self.order[..] };
+        let mut order = vec!["is_done ASC".to_string()];
+        for key in keys {
+            let (column, missing) = key.field.sql();
+            if let Some(missing) = missing {
+                order.push(missing.to_string());
+            }
+            order.push(format!("{} {}", column, if key.descending == key.field.ascending() { "DESC" } else { "ASC" }));
+        }
+        order.push("id".to_string());
+        order.join(", ")
+    }
+
+    fn to_sql(&self) -> (String, Vec<Box<dyn rusqlite::ToSql>>) {
+        let mut conditions: Vec<&str> = Vec::new();
+        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(tag) = &self.tag {
+            // Same rule as `utils::tag_matches`: lower() folds ASCII only
+            let tag = utils::normalize_tag(tag);
+            conditions.push(
+                "EXISTS (SELECT 1 FROM task_tags f WHERE f.task_id = tasks.id
+                    AND (lower(f.tag) = lower(?) OR substr(lower(f.tag), 1, length(?) + 1) = lower(?) || '/'))",
+            );
+            for _ in 0..3 {
+                values.push(Box::new(tag.clone()));
+            }
+        }
+        if let Some(done) = self.done {
+            conditions.push("is_done = ?");
+            values.push(Box::new(done));
+        }
+        if let Some(before) = self.due_before {
+            conditions.push("limit_at <= ?");
+            values.push(Box::new(before.to_rfc3339()));
+        }
+        if let Some(after) = self.due_after {
+            conditions.push("limit_at >= ?");
+            values.push(Box::new(after.to_rfc3339()));
+        }
+        if let Some(date) = self.actionable_on {
+            conditions.push("(wait_until IS NULL OR wait_until <= ?)");
+            values.push(Box::new(date.format(DATE_FORMAT).to_string()));
+        }
+        if let Some(date) = self.scheduled_by {
+            conditions.push("scheduled_on <= ?");
+            values.push(Box::new(date.format(DATE_FORMAT).to_string()));
+        }
+        if let Some((start, end)) = self.completed_between {
+            conditions.push("completed_at >= ? AND completed_at < ?");
+            values.push(Box::new(start.to_rfc3339()));
+            values.push(Box::new(end.to_rfc3339()));
+        }
+
+        let mut sql = String::from("SELECT * FROM tasks");
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY ");
+        sql.push_str(&self.order_sql());
+        if self.limit.is_some() || self.offset > 0 {
+            sql.push_str(" LIMIT ? OFFSET ?");
+            values.push(Box::new(self.limit.map_or(-1, |l| l as i64)));
+            values.push(Box::new(self.offset as i64));
+        }
+
+        (sql, values)
+    }
+}
+
+/// What tasks can be ordered by.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SortField {
+    Limit,
+    Priority,
+    Created,
+    Scheduled,
+    Title,
+    Id,
+    /// The manual order, see `move_task`.
+    Position,
+}
+
+impl SortField {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortField::Limit => "limit",
+            SortField::Priority => "priority",
+            SortField::Created => "created",
+            SortField::Scheduled => "scheduled",
+            SortField::Title => "title",
+            SortField::Id => "id",
+            SortField::Position => "manual",
+        }
+    }
+
+    /// Whether the natural order is ascending: earliest limit, schedule and
+    /// creation first, titles from A, but highest priority first.
+    fn ascending(&self) -> bool {
+        *self != SortField::Priority
+    }
+
+    /// The column to order by, and an expression that is true for tasks
+    /// without a value, which come last either way.
+    fn sql(&self) -> (&'static str, Option<&'static str>) {
+        match self {
+            SortField::Limit => ("limit_at", Some("limit_at IS NULL")),
+            SortField::Priority => ("priority", Some("priority = 0")),
+            SortField::Created => ("created_at", Some("created_at IS NULL")),
+            SortField::Scheduled => ("scheduled_on", Some("scheduled_on IS NULL")),
+            SortField::Title => ("title COLLATE NOCASE", None),
+            SortField::Id => ("id", None),
+            SortField::Position => ("position", None),
+        }
+    }
+}
+
+/// A field to sort by; `descending` reverses its natural order.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+impl SortKey {
+    /// Compares like the SQL order does, with `display` placing floating limits.
+    pub fn compare(&self, a: &Task, b: &Task, display: DisplayZone) -> Ordering {
+        let reverse = self.descending == self.field.ascending();
+        match self.field {
+            SortField::Limit => compare_values(a.limit.map(|l| l.instant(display)), b.limit.map(|l| l.instant(display)), reverse),
+            SortField::Priority => {
+                let priority = |t: &Task| Some(t.priority).filter(|p| *p != Priority::None);
+                compare_values(priority(a), priority(b), reverse)
+            }
+            SortField::Created => compare_values(a.created, b.created, reverse),
+            SortField::Scheduled => compare_values(a.scheduled, b.scheduled, reverse),
+            // Folds ASCII only, like SQLite's NOCASE
+            SortField::Title => compare_values(Some(a.title.to_ascii_lowercase()), Some(b.title.to_ascii_lowercase()), reverse),
+            SortField::Id => compare_values(Some(a.id), Some(b.id), reverse),
+            SortField::Position => compare_values(Some(a.position), Some(b.position), reverse),
+        }
+    }
+}
+
+/// Missing values last, whatever the direction.
+fn compare_values<T: Ord>(a: Option<T>, b: Option<T>, reverse: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if reverse => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (a, b) => a.is_none().cmp(&b.is_none()),
+    }
+}
+
+/// The order `TaskQuery::order` gives, for sorting tasks already loaded.
+pub fn compare_tasks(keys: &[SortKey], a: &Task, b: &Task, display: DisplayZone) -> Ordering {
+    let default = [SortKey { field: SortField::Limit, descending: false }];
+    let keys = if keys.is_empty() { &default[..] } else { keys };
+    keys.iter()
+        .fold(a.is_done.cmp(&b.is_done), |order, key| order.then_with(|| key.compare(a, b, display)))
+        .then_with(|| a.id.cmp(&b.id))
+}
+
+/// Loads the tasks matching `query`, in display order.
+pub fn query_tasks(conn: &Connection, query: &TaskQuery) -> Result<Vec<Task>> {
+    let (inner, values) = query.to_sql();
+    // Filter and page on the tasks alone so that a page never cuts a task's tags in half
+    let sql = format!(
+        "SELECT {} FROM ({}) t LEFT JOIN task_tags tt ON tt.task_id = t.id ORDER BY {}, tt.tag",
+        TASK_COLUMNS, inner, query.order_sql()
+    );
+    load_tasks(conn, &sql, rusqlite::params_from_iter(values))
+}
+
+pub fn get_tasks(conn: &Connection) -> Result<Vec<Task>> {
+    query_tasks(conn, &TaskQuery::new())
+}
+
 pub fn get_task(conn: &Connection, id: i64) -> Result<Option<Task>> {
-    let mut stmt = conn.prepare("SELECT id, title, is_done, limit_at, description FROM tasks WHERE id = ?")?;
-    let mut task_iter = stmt.query_map(params![id], |row| {
-        let limit_str: Option<String> = row.get(3)?;
-        let limit = limit_str.and_then(|s| DateTime::parse_from_rfc3339(&s).ok().map(|dt| dt.with_timezone(&Utc)));
-        
-        Ok((row.get(0)?, row.get(1)?, row.get(2)?, limit, row.get(4)?))
-    })?;
+    let sql = format!(
+        "SELECT {} FROM tasks t LEFT JOIN task_tags tt ON tt.task_id = t.id
+         WHERE t.id = ? ORDER BY tt.tag",
+        TASK_COLUMNS
+    );
+    Ok(load_tasks(conn, &sql, params![id])?.pop())
+}
 
-    if let Some(res) = task_iter.next() {
-        let (id, title, is_done, limit, description) = res?;
-        
-        // Tags
-        let mut tag_stmt = conn.prepare("SELECT tag FROM task_tags WHERE task_id = ?")?;
-        let tags = tag_stmt.query_map(params![id], |r| r.get(0))?.collect::<Result<Vec<String>, _>>()?;
+/// SQLite's `data_version`, which changes whenever another connection commits
+/// to the database. Writes made through `conn` itself leave it untouched.
+pub fn data_version(conn: &Connection) -> Result<i64> {
+    conn.pragma_query_value(None, "data_version", |row| row.get(0))
+}
 
-        Ok(Some(Task {
-            id,
-            title,
-            is_done,
-            limit,
-            description,
-            tags,
-        }))
-    } else {
-        Ok(None)
+/// Puts task `id` right before (or with `after`, right after) `target` in the
+/// manual order, shifting the tasks behind it down by one.
+pub fn move_task(conn: &Connection, id: i64, target: i64, after: bool) -> Result<()> {
+    if id == target {
+        return Ok(());
     }
+    let tx = conn.unchecked_transaction()?;
+    let target_position: i64 = tx.query_row("SELECT position FROM tasks WHERE id = ?", params![target], |row| row.get(0))?;
+    let position = if after { target_position + 1 } else { target_position };
+    tx.execute("UPDATE tasks SET position = position + 1 WHERE position >= ? AND id != ?", params![position, id])?;
+    tx.execute("UPDATE tasks SET position = ? WHERE id = ?", params![position, id])?;
+    tx.commit()
 }
 
 pub fn delete_task(conn: &Connection, id: i64) -> Result<()> {
@@ -141,23 +896,899 @@ pub fn delete_task(conn: &Connection, id: i64) -> Result<()> {
     Ok(())
 }
 
-pub fn update_task(conn: &Connection, task: &Task) -> Result<()> {
+/// Marks a task done or open without touching its other fields, so edits made
+/// elsewhere since it was loaded survive. Like `update_task`, completing it
+/// leaves the workflow.
+pub fn set_done(conn: &Connection, id: i64, done: bool) -> Result<()> {
     conn.execute(
-        "UPDATE tasks SET title = ?, is_done = ?, limit_at = ?, description = ? WHERE id = ?",
+        "UPDATE tasks SET is_done = ?, completed_at = CASE WHEN ? THEN COALESCE(completed_at, ?) END,
+         state = CASE WHEN ? THEN NULL ELSE state END WHERE id = ?",
+        params![done, done, Utc::now().to_rfc3339(), done, id],
+    )?;
+    Ok(())
+}
+
+pub fn update_task(conn: &Connection, task: &Task) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    let (limit_at, limit_tz, limit_local) = deadline_columns(task.limit.as_ref(), display_zone(&tx)?);
+    tx.execute(
+        "UPDATE tasks SET title = ?, is_done = ?, completed_at = CASE WHEN ? THEN COALESCE(completed_at, ?) END,
+         state = ?, limit_at = ?, limit_tz = ?, limit_local = ?, scheduled_on = ?, wait_until = ?,
+         description = ?, priority = ?, estimate_minutes = ? WHERE id = ?",
         params![
             task.title,
             task.is_done,
-            task.limit.map(|t| t.to_rfc3339()),
+            task.is_done,
+            Utc::now().to_rfc3339(),
+            open_state(task),
+            limit_at,
+            limit_tz,
+            limit_local,
+            date_to_sql(task.scheduled),
+            date_to_sql(task.wait_until),
             task.description,
-            task.id
+            task.priority,
+            task.estimate.map(|e| e.num_minutes()),
+            task.id,
         ],
     )?;
 
     // Update Tags
-    conn.execute("DELETE FROM task_tags WHERE task_id = ?", params![task.id])?;
+    tx.execute("DELETE FROM task_tags WHERE task_id = ?", params![task.id])?;
     for tag in &task.tags {
-        conn.execute("INSERT INTO task_tags (task_id, tag) VALUES (?, ?)", params![task.id, tag])?;
+        tx.execute("INSERT INTO task_tags (task_id, tag) VALUES (?, ?)", params![task.id, tag])?;
     }
+    tx.execute("DELETE FROM task_deps WHERE task_id = ?", params![task.id])?;
+    insert_dependencies(&tx, task.id, &task.dependencies)?;
+
+    tx.commit()
+}
+
+/// All tags in use with the number of tasks carrying each, sorted by name.
+pub fn get_tag_counts(conn: &Connection) -> Result<Vec<(String, usize)>> {
+    let mut stmt = conn.prepare("SELECT tag, COUNT(*) FROM task_tags GROUP BY tag ORDER BY tag")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)))?;
+    rows.collect()
+}
+
+/// Every `(task_id, tag)` pair, for building the tag hierarchy.
+pub fn get_tag_assignments(conn: &Connection) -> Result<Vec<(i64, String)>> {
+    let mut stmt = conn.prepare("SELECT task_id, tag FROM task_tags")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
 
+/// Moves every task tagged `from` over to `into`, returning the number of tasks touched.
+/// Tasks that already carry both simply lose `from`. Metadata of `from` is kept
+/// only if `into` has none. Merging a tag into itself (see `utils::same_tag`)
+/// is an error, as it would strip the tag from every task.
+pub fn merge_tags(conn: &Connection, from: &str, into: &str) -> Result<usize> {
+    if utils::same_tag(from, into) {
+        return Err(constraint_error(format!("can't merge tag '{}' into itself", from)));
+    }
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "INSERT OR IGNORE INTO task_tags (task_id, tag) SELECT task_id, ? FROM task_tags WHERE tag = ?",
+        params![into, from],
+    )?;
+    let count = tx.execute("DELETE FROM task_tags WHERE tag = ?", params![from])?;
+    tx.execute(
+        "INSERT OR IGNORE INTO tags (name, color, description, icon, default_priority)
+         SELECT ?, color, description, icon, default_priority FROM tags WHERE name = ?",
+        params![into, from],
+    )?;
+    tx.execute("DELETE FROM tags WHERE name = ?", params![from])?;
+    tx.commit()?;
+    Ok(count)
+}
+
+/// Renames a tag across all tasks. Renaming onto an existing tag merges the two,
+/// except that a change of case only renames.
+pub fn rename_tag(conn: &Connection, old: &str, new: &str) -> Result<usize> {
+    if !utils::same_tag(old, new) {
+        return merge_tags(conn, old, new);
+    }
+    let tx = conn.unchecked_transaction()?;
+    let count = tx.execute("UPDATE task_tags SET tag = ? WHERE tag = ?", params![new, old])?;
+    tx.execute("UPDATE tags SET name = ? WHERE name = ?", params![new, old])?;
+    tx.commit()?;
+    Ok(count)
+}
+
+/// Removes a tag from all tasks, returning the number of tasks touched.
+pub fn delete_tag(conn: &Connection, tag: &str) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let count = tx.execute("DELETE FROM task_tags WHERE tag = ?", params![tag])?;
+    tx.execute("DELETE FROM tags WHERE name = ?", params![tag])?;
+    tx.commit()?;
+    Ok(count)
+}
+
+/// Metadata of every tag that has some, keyed by tag name.
+pub fn get_tag_metas(conn: &Connection) -> Result<HashMap<String, TagMeta>> {
+    let mut stmt = conn.prepare("SELECT name, color, description, icon, default_priority FROM tags")?;
+    let rows = stmt.query_map([], |row| {
+        Ok(TagMeta {
+            name: row.get(0)?,
+            color: row.get(1)?,
+            description: row.get(2)?,
+            icon: row.get(3)?,
+            default_priority: row.get(4)?,
+        })
+    })?;
+    rows.map(|r| r.map(|m| (m.name.clone(), m))).collect()
+}
+
+pub fn get_tag_meta(conn: &Connection, name: &str) -> Result<Option<TagMeta>> {
+    conn.query_row(
+        "SELECT name, color, description, icon, default_priority FROM tags WHERE name = ?",
+        params![name],
+        |row| {
+            Ok(TagMeta {
+                name: row.get(0)?,
+                color: row.get(1)?,
+                description: row.get(2)?,
+                icon: row.get(3)?,
+                default_priority: row.get(4)?,
+            })
+        },
+    )
+    .optional()
+}
+
+pub fn set_tag_meta(conn: &Connection, meta: &TagMeta) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO tags (name, color, description, icon, default_priority) VALUES (?, ?, ?, ?, ?)",
+        params![meta.name, meta.color, meta.description, meta.icon, meta.default_priority],
+    )?;
     Ok(())
 }
+
+/// Metadata for `tag`, falling back to the nearest ancestor that has some, so
+/// `work/api` is shown in the color configured for `work`.
+pub fn find_tag_meta<'a>(metas: &'a HashMap<String, TagMeta>, tag: &str) -> Option<&'a TagMeta> {
+    let mut path = tag;
+    loop {
+        if let Some(meta) = metas.get(path) {
+            return Some(meta);
+        }
+        path = &path[..path.rfind('/')?];
+    }
+}
+
+fn time_from_sql(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value).map_or(DateTime::UNIX_EPOCH, |dt| dt.with_timezone(&Utc))
+}
+
+fn read_time_entry(row: &rusqlite::Row) -> Result<TimeEntry> {
+    Ok(TimeEntry {
+        id: row.get("id")?,
+        task_id: row.get("task_id")?,
+        started: time_from_sql(&row.get::<_, String>("started_at")?),
+        stopped: row.get::<_, Option<String>>("stopped_at")?.map(|s| time_from_sql(&s)),
+    })
+}
+
+/// The entry being tracked right now, if any.
+pub fn running_timer(conn: &Connection) -> Result<Option<TimeEntry>> {
+    conn.query_row("SELECT * FROM time_entries WHERE stopped_at IS NULL", [], read_time_entry)
+        .optional()
+}
+
+/// Starts tracking time on a task at `now`, stopping the running timer first.
+/// Returns the entry that was stopped.
+pub fn start_timer(conn: &Connection, task_id: i64, now: DateTime<Utc>) -> Result<Option<TimeEntry>> {
+    let tx = conn.unchecked_transaction()?;
+    let stopped = stop_timer(&tx, now)?;
+    tx.execute(
+        "INSERT INTO time_entries (task_id, started_at) VALUES (?, ?)",
+        params![task_id, now.to_rfc3339()],
+    )?;
+    tx.commit()?;
+    Ok(stopped)
+}
+
+/// Stops the running timer at `now`, returning the finished entry.
+pub fn stop_timer(conn: &Connection, now: DateTime<Utc>) -> Result<Option<TimeEntry>> {
+    let Some(mut entry) = running_timer(conn)? else {
+        return Ok(None);
+    };
+    conn.execute("UPDATE time_entries SET stopped_at = ? WHERE id = ?", params![now.to_rfc3339(), entry.id])?;
+    entry.stopped = Some(now);
+    Ok(Some(entry))
+}
+
+/// Time entries overlapping `start..end`, running ones included, oldest first.
+pub fn get_time_entries_between(conn: &Connection, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<TimeEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT * FROM time_entries WHERE started_at < ? AND (stopped_at IS NULL OR stopped_at > ?) ORDER BY started_at",
+    )?;
+    let rows = stmt.query_map(params![end.to_rfc3339(), start.to_rfc3339()], read_time_entry)?;
+    rows.collect()
+}
+
+/// All time entries of a task, oldest first.
+pub fn get_time_entries(conn: &Connection, task_id: i64) -> Result<Vec<TimeEntry>> {
+    let mut stmt = conn.prepare("SELECT * FROM time_entries WHERE task_id = ? ORDER BY started_at")?;
+    let rows = stmt.query_map(params![task_id], read_time_entry)?;
+    rows.collect()
+}
+
+/// Total time of the finished entries of every task that has some.
+pub fn tracked_totals(conn: &Connection) -> Result<HashMap<i64, chrono::Duration>> {
+    let mut stmt = conn.prepare("SELECT * FROM time_entries WHERE stopped_at IS NOT NULL")?;
+    let mut totals: HashMap<i64, chrono::Duration> = HashMap::new();
+    for entry in stmt.query_map([], read_time_entry)? {
+        let entry = entry?;
+        *totals.entry(entry.task_id).or_default() += entry.duration(Utc::now());
+    }
+    Ok(totals)
+}
+
+/// Records a finished pomodoro on a task.
+pub fn add_pomodoro(conn: &Connection, task_id: i64, at: DateTime<Utc>) -> Result<()> {
+    conn.execute(
+        "INSERT INTO pomodoros (task_id, completed_at) VALUES (?, ?)",
+        params![task_id, at.to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Number of finished pomodoros per task.
+pub fn pomodoro_counts(conn: &Connection) -> Result<HashMap<i64, i64>> {
+    let mut stmt = conn.prepare("SELECT task_id, COUNT(*) FROM pomodoros GROUP BY task_id")?;
+    let counts = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    counts.collect()
+}
+
+pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>> {
+    conn.query_row("SELECT value FROM settings WHERE key = ?", params![key], |row| row.get(0))
+        .optional()
+}
+
+pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)", params![key, value])?;
+    Ok(())
+}
+
+/// Removes a setting, returning whether it was set.
+pub fn unset_setting(conn: &Connection, key: &str) -> Result<bool> {
+    Ok(conn.execute("DELETE FROM settings WHERE key = ?", params![key])? > 0)
+}
+
+/// All settings that are set, sorted by key.
+pub fn get_settings(conn: &Connection) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare("SELECT key, value FROM settings ORDER BY key")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// The `timezone` setting, falling back to the system zone.
+pub fn display_zone(conn: &Connection) -> Result<DisplayZone> {
+    Ok(get_setting(conn, "timezone")?
+        .and_then(|name| DisplayZone::parse(&name))
+        .unwrap_or_default())
+}
+
+/// Work `sakd plan` fits into a day (the `capacity` setting), 8 hours by default.
+pub fn daily_capacity(conn: &Connection) -> Result<chrono::Duration> {
+    Ok(get_setting(conn, "capacity")?
+        .and_then(|value| utils::parse_duration(&value))
+        .unwrap_or_else(|| chrono::Duration::minutes(utils::WORKDAY_MINUTES)))
+}
+
+/// Lengths of pomodoro work phases and breaks (the `pomodoro-work` and
+/// `pomodoro-break` settings), 25 and 5 minutes by default.
+pub fn pomodoro_lengths(conn: &Connection) -> Result<(chrono::Duration, chrono::Duration)> {
+    let length = |key, default| -> Result<chrono::Duration> {
+        Ok(get_setting(conn, key)?
+            .and_then(|value| utils::parse_duration(&value))
+            .unwrap_or_else(|| chrono::Duration::minutes(default)))
+    };
+    Ok((length("pomodoro-work", pomodoro::WORK_MINUTES)?, length("pomodoro-break", pomodoro::BREAK_MINUTES)?))
+}
+
+/// The configured workflow states, or the default ones.
+pub fn workflow(conn: &Connection) -> Result<Workflow> {
+    Ok(get_setting(conn, "workflow")?
+        .and_then(|value| Workflow::parse(&value))
+        .unwrap_or_default())
+}
+
+/// Re-resolves the stored instant of floating and all-day deadlines in the
+/// current display zone, so that SQL range filters and ordering follow it.
+/// Returns the number of tasks updated.
+pub fn refresh_floating_limits(conn: &Connection) -> Result<usize> {
+    let display = display_zone(conn)?;
+    let tx = conn.unchecked_transaction()?;
+    let rows: Vec<(i64, Option<String>, String, String)> = {
+        let mut stmt = tx.prepare(
+            "SELECT id, limit_at, limit_tz, limit_local FROM tasks WHERE limit_tz IN (?, ?) AND limit_local IS NOT NULL",
+        )?;
+        let rows = stmt.query_map(params![FLOATING, ALL_DAY], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+        rows.collect::<Result<_>>()?
+    };
+
+    let mut count = 0;
+    for (id, at, kind, local) in rows {
+        let Some(deadline) = local_deadline(&kind, &local) else { continue };
+        let resolved = deadline.instant(display).to_rfc3339();
+        if at.as_deref() != Some(resolved.as_str()) {
+            tx.execute("UPDATE tasks SET limit_at = ? WHERE id = ?", params![resolved, id])?;
+            count += 1;
+        }
+    }
+    tx.commit()?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn task(title: &str, limit: Option<DateTime<Utc>>, tags: Vec<String>) -> Task {
+        let mut task = Task::new(title);
+        task.limit = limit.map(|at| Deadline::At(at, None));
+        task.tags = tags;
+        task
+    }
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_get_tasks_groups_tags() {
+        let conn = test_conn();
+        let a = add_task(&conn, &task("a", None, vec!["work".into(), "api".into()])).unwrap();
+        let b = add_task(&conn, &task("b", None, vec![])).unwrap();
+        let c = add_task(&conn, &task("c", None, vec!["home".into()])).unwrap();
+
+        let tasks = get_tasks(&conn).unwrap();
+        assert_eq!(tasks.len(), 3);
+
+        let find = |id| tasks.iter().find(|t| t.id == id).unwrap();
+        assert_eq!(find(a).tags, vec!["api", "work"]);
+        assert!(find(b).tags.is_empty());
+        assert_eq!(find(c).tags, vec!["home"]);
+
+        let single = get_task(&conn, a).unwrap().unwrap();
+        assert_eq!(single.tags, vec!["api", "work"]);
+        assert!(get_task(&conn, 999).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_sort_orders() {
+        let conn = test_conn();
+        let at = |d| Some(Utc.with_ymd_and_hms(2026, 3, d, 12, 0, 0).unwrap());
+        let mut t = task("beta", at(5), vec![]);
+        t.priority = Priority::Low;
+        let beta = add_task(&conn, &t).unwrap();
+        let mut t = task("Alpha", None, vec![]);
+        t.priority = Priority::High;
+        let alpha = add_task(&conn, &t).unwrap();
+        let gamma = add_task(&conn, &task("gamma", at(3), vec![])).unwrap();
+        let mut t = task("delta", at(1), vec![]);
+        t.is_done = true;
+        let delta = add_task(&conn, &t).unwrap();
+
+        let display = DisplayZone::Local;
+        let cases = [
+            ("limit", vec![gamma, beta, alpha, delta]),
+            ("-limit", vec![beta, gamma, alpha, delta]),
+            ("priority,title", vec![alpha, beta, gamma, delta]),
+            ("-priority", vec![beta, alpha, gamma, delta]),
+            ("title", vec![alpha, beta, gamma, delta]),
+            ("-id", vec![gamma, alpha, beta, delta]),
+        ];
+        for (spec, expected) in cases {
+            let keys = utils::parse_sort(spec).unwrap();
+            let mut tasks = query_tasks(&conn, &TaskQuery::new().order(keys.clone())).unwrap();
+            assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), expected, "{}", spec);
+
+            // Sorting in memory agrees with SQL
+            tasks.reverse();
+            tasks.sort_by(|a, b| compare_tasks(&keys, a, b, display));
+            assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), expected, "{}", spec);
+        }
+
+        // Non-ASCII titles sort the same way in both
+        let conn = test_conn();
+        for title in ["éclair", "Zebra", "Émile", "apple"] {
+            add_task(&conn, &task(title, None, vec![])).unwrap();
+        }
+        let keys = utils::parse_sort("title").unwrap();
+        let mut tasks = query_tasks(&conn, &TaskQuery::new().order(keys.clone())).unwrap();
+        let titles = |tasks: &[Task]| tasks.iter().map(|t| t.title.clone()).collect::<Vec<_>>();
+        assert_eq!(titles(&tasks), ["apple", "Zebra", "Émile", "éclair"]);
+        tasks.reverse();
+        tasks.sort_by(|a, b| compare_tasks(&keys, a, b, display));
+        assert_eq!(titles(&tasks), ["apple", "Zebra", "Émile", "éclair"]);
+    }
+
+    #[test]
+    fn test_manual_order() {
+        let conn = test_conn();
+        let ids: Vec<i64> = ["a", "b", "c", "d"].iter().map(|t| add_task(&conn, &Task::new(t)).unwrap()).collect();
+        let manual = || {
+            let query = TaskQuery::new().order(utils::parse_sort("manual").unwrap());
+            query_tasks(&conn, &query).unwrap().iter().map(|t| t.title.clone()).collect::<Vec<_>>().join("")
+        };
+        assert_eq!(manual(), "abcd");
+
+        move_task(&conn, ids[3], ids[0], false).unwrap();
+        assert_eq!(manual(), "dabc");
+        move_task(&conn, ids[3], ids[2], true).unwrap();
+        assert_eq!(manual(), "abcd");
+        move_task(&conn, ids[0], ids[1], true).unwrap();
+        assert_eq!(manual(), "bacd");
+
+        // New tasks go last
+        add_task(&conn, &Task::new("e")).unwrap();
+        assert_eq!(manual(), "bacde");
+
+        // Rows written without a position still load
+        conn.execute("INSERT INTO tasks (title) VALUES ('raw')", []).unwrap();
+        assert_eq!(get_tasks(&conn).unwrap().len(), 6);
+
+        // A nullable column from an older version is filled in on open
+        let old = Connection::open_in_memory().unwrap();
+        old.execute_batch(
+            "CREATE TABLE tasks (id INTEGER PRIMARY KEY, title TEXT NOT NULL, is_done BOOLEAN NOT NULL DEFAULT 0,
+                 limit_at TEXT, description TEXT, position INTEGER);
+             INSERT INTO tasks (title) VALUES ('old');",
+        ).unwrap();
+        init_schema(&old).unwrap();
+        let task = &get_tasks(&old).unwrap()[0];
+        assert_eq!(task.position, task.id);
+    }
+
+    #[test]
+    fn test_time_tracking() {
+        let conn = test_conn();
+        let a = add_task(&conn, &Task::new("a")).unwrap();
+        let b = add_task(&conn, &Task::new("b")).unwrap();
+        let at = |h, m| Utc.with_ymd_and_hms(2026, 3, 5, h, m, 0).unwrap();
+
+        assert!(start_timer(&conn, a, at(9, 0)).unwrap().is_none());
+        // Starting another task stops the first
+        let stopped = start_timer(&conn, b, at(9, 40)).unwrap().unwrap();
+        assert_eq!((stopped.task_id, stopped.duration(at(12, 0)).num_minutes()), (a, 40));
+        let running = running_timer(&conn).unwrap().unwrap();
+        assert_eq!((running.task_id, running.duration(at(10, 0)).num_minutes()), (b, 20));
+
+        assert_eq!(stop_timer(&conn, at(10, 30)).unwrap().unwrap().task_id, b);
+        assert!(stop_timer(&conn, at(11, 0)).unwrap().is_none());
+        start_timer(&conn, a, at(13, 0)).unwrap();
+        stop_timer(&conn, at(13, 5)).unwrap();
+
+        let totals = tracked_totals(&conn).unwrap();
+        assert_eq!(totals[&a].num_minutes(), 45);
+        assert_eq!(totals[&b].num_minutes(), 50);
+        assert_eq!(get_time_entries(&conn, a).unwrap().len(), 2);
+
+        delete_task(&conn, a).unwrap();
+        assert!(get_time_entries(&conn, a).unwrap().is_empty());
+
+        // The database allows only one running entry
+        start_timer(&conn, b, at(14, 0)).unwrap();
+        let insert = "INSERT INTO time_entries (task_id, started_at) VALUES (?, ?)";
+        assert!(conn.execute(insert, params![b, at(14, 30).to_rfc3339()]).is_err());
+
+        // Duplicates from before the index stop when the next one started
+        conn.execute("DROP INDEX idx_time_entries_running", []).unwrap();
+        conn.execute(insert, params![b, at(15, 0).to_rfc3339()]).unwrap();
+        init_schema(&conn).unwrap();
+        let entries = get_time_entries(&conn, b).unwrap();
+        let stops: Vec<Option<DateTime<Utc>>> = entries.iter().map(|e| e.stopped).collect();
+        assert_eq!(stops[stops.len() - 2..], [Some(at(15, 0)), None]);
+    }
+
+    #[test]
+    fn test_completion_time() {
+        let conn = test_conn();
+        let id = add_task(&conn, &Task::new("a")).unwrap();
+        let mut t = get_task(&conn, id).unwrap().unwrap();
+        assert!(t.created.is_some() && t.completed.is_none());
+
+        t.is_done = true;
+        update_task(&conn, &t).unwrap();
+        let done = get_task(&conn, id).unwrap().unwrap().completed.unwrap();
+        // Saving a done task again keeps the first completion time
+        update_task(&conn, &t).unwrap();
+        assert_eq!(get_task(&conn, id).unwrap().unwrap().completed, Some(done));
+
+        let hour = chrono::Duration::hours(1);
+        let range = |start, end| TaskQuery::new().completed_between(Some((start, end)));
+        assert_eq!(query_tasks(&conn, &range(done - hour, done + hour)).unwrap().len(), 1);
+        assert!(query_tasks(&conn, &range(done + hour, done + hour * 2)).unwrap().is_empty());
+
+        t.is_done = false;
+        update_task(&conn, &t).unwrap();
+        assert!(get_task(&conn, id).unwrap().unwrap().completed.is_none());
+
+        // Toggling keeps changes made since `t` was loaded
+        let mut renamed = t.clone();
+        renamed.title = "renamed".into();
+        update_task(&conn, &renamed).unwrap();
+        set_done(&conn, id, true).unwrap();
+        let stored = get_task(&conn, id).unwrap().unwrap();
+        assert_eq!((stored.title.as_str(), stored.is_done, stored.completed.is_some()), ("renamed", true, true));
+        set_done(&conn, id, false).unwrap();
+        assert!(get_task(&conn, id).unwrap().unwrap().completed.is_none());
+    }
+
+    #[test]
+    fn test_estimates() {
+        let conn = test_conn();
+        let mut t = Task::new("a");
+        t.estimate = Some(chrono::Duration::minutes(90));
+        let id = add_task(&conn, &t).unwrap();
+        let mut t = get_task(&conn, id).unwrap().unwrap();
+        assert_eq!(t.estimate, Some(chrono::Duration::minutes(90)));
+        t.estimate = None;
+        update_task(&conn, &t).unwrap();
+        assert_eq!(get_task(&conn, id).unwrap().unwrap().estimate, None);
+
+        assert_eq!(daily_capacity(&conn).unwrap(), chrono::Duration::minutes(crate::utils::WORKDAY_MINUTES));
+        set_setting(&conn, "capacity", "6h 00m").unwrap();
+        assert_eq!(daily_capacity(&conn).unwrap(), chrono::Duration::hours(6));
+    }
+
+    #[test]
+    fn test_pomodoros() {
+        let conn = test_conn();
+        let a = add_task(&conn, &Task::new("a")).unwrap();
+        let b = add_task(&conn, &Task::new("b")).unwrap();
+        add_pomodoro(&conn, a, Utc::now()).unwrap();
+        add_pomodoro(&conn, a, Utc::now()).unwrap();
+        add_pomodoro(&conn, b, Utc::now()).unwrap();
+        assert_eq!(pomodoro_counts(&conn).unwrap(), HashMap::from([(a, 2), (b, 1)]));
+
+        delete_task(&conn, a).unwrap();
+        assert_eq!(pomodoro_counts(&conn).unwrap(), HashMap::from([(b, 1)]));
+
+        set_setting(&conn, "pomodoro-work", "50m").unwrap();
+        assert_eq!(pomodoro_lengths(&conn).unwrap(), (chrono::Duration::minutes(50), chrono::Duration::minutes(5)));
+    }
+
+    #[test]
+    fn test_writes_are_atomic_and_cascade() {
+        let conn = test_conn();
+
+        // A duplicate tag violates the primary key; the task row must not survive
+        assert!(add_task(&conn, &task("dup", None, vec!["a".into(), "a".into()])).is_err());
+        assert!(get_tasks(&conn).unwrap().is_empty());
+
+        let id = add_task(&conn, &task("task", None, vec!["a".into()])).unwrap();
+        let mut task = get_task(&conn, id).unwrap().unwrap();
+        task.title = "renamed".into();
+        task.tags = vec!["b".into(), "b".into()];
+        assert!(update_task(&conn, &task).is_err());
+        let unchanged = get_task(&conn, id).unwrap().unwrap();
+        assert_eq!(unchanged.title, "task");
+        assert_eq!(unchanged.tags, vec!["a"]);
+
+        delete_task(&conn, id).unwrap();
+        let orphans: i64 = conn.query_row("SELECT COUNT(*) FROM task_tags", [], |r| r.get(0)).unwrap();
+        assert_eq!(orphans, 0);
+    }
+
+    #[test]
+    fn test_dependency_cycles_are_rejected() {
+        let conn = test_conn();
+        let a = add_task(&conn, &Task::new("a")).unwrap();
+        let mut b = Task::new("b");
+        b.dependencies = vec![a];
+        let b = add_task(&conn, &b).unwrap();
+        let mut c = Task::new("c");
+        c.dependencies = vec![b];
+        let c = add_task(&conn, &c).unwrap();
+
+        assert_eq!(dependency_cycle(&conn, a, &[c]).unwrap(), Some(c));
+        assert_eq!(dependency_cycle(&conn, a, &[a]).unwrap(), Some(a));
+        assert_eq!(dependency_cycle(&conn, c, &[a]).unwrap(), None);
+
+        // a -> c -> b -> a, and a -> a
+        let mut task = get_task(&conn, a).unwrap().unwrap();
+        for deps in [vec![c], vec![a]] {
+            task.dependencies = deps;
+            assert!(update_task(&conn, &task).is_err());
+            assert!(get_task(&conn, a).unwrap().unwrap().dependencies.is_empty());
+        }
+        // Replacing c's own dependency isn't a cycle
+        let mut task = get_task(&conn, c).unwrap().unwrap();
+        task.dependencies = vec![a];
+        update_task(&conn, &task).unwrap();
+        assert_eq!(open_dependencies(&conn).unwrap()[&c], vec![a]);
+    }
+
+    #[test]
+    fn test_data_version_tracks_other_connections() {
+        let path = std::env::temp_dir().join(format!("sakd-data-version-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let tui = Connection::open(&path).unwrap();
+        init_schema(&tui).unwrap();
+        let cli = Connection::open(&path).unwrap();
+
+        let before = data_version(&tui).unwrap();
+        add_task(&tui, &task("own write", None, vec![])).unwrap();
+        assert_eq!(data_version(&tui).unwrap(), before);
+
+        add_task(&cli, &task("external write", None, vec![])).unwrap();
+        assert_ne!(data_version(&tui).unwrap(), before);
+
+        drop((tui, cli));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_tag_management() {
+        let conn = test_conn();
+        let a = add_task(&conn, &task("a", None, vec!["wrk".into(), "work".into()])).unwrap();
+        let b = add_task(&conn, &task("b", None, vec!["wrk".into()])).unwrap();
+        add_task(&conn, &task("c", None, vec!["home".into()])).unwrap();
+
+        assert_eq!(
+            get_tag_counts(&conn).unwrap(),
+            vec![("home".to_string(), 1), ("work".to_string(), 1), ("wrk".to_string(), 2)]
+        );
+
+        assert_eq!(merge_tags(&conn, "wrk", "work").unwrap(), 2);
+        assert_eq!(get_task(&conn, a).unwrap().unwrap().tags, vec!["work"]);
+        assert_eq!(get_task(&conn, b).unwrap().unwrap().tags, vec!["work"]);
+
+        // Merging a tag into itself would strip it from every task
+        set_tag_meta(&conn, &TagMeta { name: "work".into(), color: Some("red".into()), ..TagMeta::default() }).unwrap();
+        for into in ["work", "Work", " work/ "] {
+            assert!(merge_tags(&conn, "work", into).is_err());
+        }
+        assert_eq!(get_task(&conn, a).unwrap().unwrap().tags, vec!["work"]);
+        assert_eq!(get_tag_metas(&conn).unwrap()["work"].color.as_deref(), Some("red"));
+
+        assert_eq!(rename_tag(&conn, "home", "Home").unwrap(), 1);
+        assert_eq!(rename_tag(&conn, "Home", "house").unwrap(), 1);
+        assert_eq!(delete_tag(&conn, "work").unwrap(), 2);
+        assert_eq!(get_tag_counts(&conn).unwrap(), vec![("house".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_tag_meta_and_default_priority() {
+        let conn = test_conn();
+        set_tag_meta(&conn, &TagMeta {
+            name: "work".into(),
+            color: Some("blue".into()),
+            default_priority: Priority::Medium,
+            ..Default::default()
+        }).unwrap();
+
+        let metas = get_tag_metas(&conn).unwrap();
+        assert_eq!(find_tag_meta(&metas, "work/api").unwrap().color.as_deref(), Some("blue"));
+        assert!(find_tag_meta(&metas, "homework").is_none());
+
+        let inherited = add_task(&conn, &task("a", None, vec!["work/api".into()])).unwrap();
+        assert_eq!(get_task(&conn, inherited).unwrap().unwrap().priority, Priority::Medium);
+
+        let mut explicit = task("b", None, vec!["work".into()]);
+        explicit.priority = Priority::High;
+        let explicit = add_task(&conn, &explicit).unwrap();
+        assert_eq!(get_task(&conn, explicit).unwrap().unwrap().priority, Priority::High);
+
+        rename_tag(&conn, "work", "job").unwrap();
+        assert_eq!(get_tag_meta(&conn, "job").unwrap().unwrap().color.as_deref(), Some("blue"));
+        assert!(get_tag_meta(&conn, "work").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_query_tasks_filters_and_pages() {
+        let conn = test_conn();
+        let now = Utc::now();
+        for i in 0..5 {
+            let limit = Some(now + chrono::Duration::days(i));
+            add_task(&conn, &task(&format!("work {}", i), limit, vec!["Work/API".into()])).unwrap();
+        }
+        let done = add_task(&conn, &task("home", None, vec!["home".into()])).unwrap();
+        let mut task = get_task(&conn, done).unwrap().unwrap();
+        task.is_done = true;
+        update_task(&conn, &task).unwrap();
+
+        let work = query_tasks(&conn, &TaskQuery::new().tag(Some("work".into()))).unwrap();
+        assert_eq!(work.len(), 5);
+        assert!(query_tasks(&conn, &TaskQuery::new().tag(Some("api".into()))).unwrap().is_empty());
+        assert!(query_tasks(&conn, &TaskQuery::new().tag(Some("wor".into()))).unwrap().is_empty());
+
+        let open = query_tasks(&conn, &TaskQuery::new().done(Some(false))).unwrap();
+        assert!(open.iter().all(|t| !t.is_done));
+        assert_eq!(open.len(), 5);
+
+        let soon = query_tasks(&conn, &TaskQuery::new().due_before(Some(now + chrono::Duration::hours(36)))).unwrap();
+        assert_eq!(soon.iter().map(|t| t.title.as_str()).collect::<Vec<_>>(), vec!["work 0", "work 1"]);
+
+        let page = query_tasks(&conn, &TaskQuery::new().limit(Some(2)).offset(3)).unwrap();
+        assert_eq!(page.iter().map(|t| t.title.as_str()).collect::<Vec<_>>(), vec!["work 3", "work 4"]);
+        assert!(page.iter().all(|t| t.tags == vec!["Work/API"]));
+    }
+
+    #[test]
+    fn test_tag_filter_matches_in_memory_filter() {
+        // Both fold ASCII case only, so non-ASCII tags match the same tasks
+        let conn = test_conn();
+        for tag in ["Ärger", "ärger", "Ärger/Amt", "ÄRGER"] {
+            add_task(&conn, &task(tag, None, vec![tag.into()])).unwrap();
+        }
+        let all = get_tasks(&conn).unwrap();
+        for filter in ["Ärger", "ärger", "Ärger/amt", " ÄRGER/ "] {
+            let titles = |tasks: Vec<&Task>| tasks.iter().map(|t| t.title.clone()).collect::<Vec<_>>();
+            let sql = query_tasks(&conn, &TaskQuery::new().tag(Some(filter.into()))).unwrap();
+            let memory: Vec<&Task> = all.iter().filter(|t| t.tags.iter().any(|tag| utils::tag_matches(tag, filter))).collect();
+            assert_eq!(titles(sql.iter().collect()), titles(memory), "{}", filter);
+        }
+        let matching = query_tasks(&conn, &TaskQuery::new().tag(Some("Ärger".into()))).unwrap();
+        assert_eq!(matching.iter().map(|t| t.title.as_str()).collect::<Vec<_>>(), ["Ärger", "Ärger/Amt", "ÄRGER"]);
+    }
+
+    #[test]
+    fn test_deadline_zones() {
+        let conn = test_conn();
+        let tokyo: Tz = "Asia/Tokyo".parse().unwrap();
+        let naive = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        set_setting(&conn, "timezone", "Asia/Tokyo").unwrap();
+        let display = display_zone(&conn).unwrap();
+        assert_eq!(display, DisplayZone::Named(tokyo));
+
+        // Floating end of day, and 15:00 New York time
+        let floating = Deadline::Floating(naive("2026-03-05 23:59"));
+        let mut t = Task::new("floating");
+        t.limit = Some(floating);
+        let a = add_task(&conn, &t).unwrap();
+        let new_york = Deadline::Floating(naive("2026-03-05 15:00"))
+            .rezone(DeadlineZone::parse("America/New_York").unwrap(), display);
+        t.title = "zoned".into();
+        t.limit = Some(new_york);
+        let b = add_task(&conn, &t).unwrap();
+
+        assert_eq!(get_task(&conn, a).unwrap().unwrap().limit, Some(floating));
+        let zoned = get_task(&conn, b).unwrap().unwrap().limit.unwrap();
+        assert_eq!(zoned, new_york);
+        assert_eq!(zoned.local(display), naive("2026-03-06 05:00"));
+        assert_eq!(zoned.own_local(display), naive("2026-03-05 15:00"));
+
+        // Range filters see the floating deadline at 23:59 in the display zone
+        let due = |zone: DisplayZone| {
+            let query = TaskQuery::new().due_before(Some(zone.resolve(naive("2026-03-05 23:59"))));
+            query_tasks(&conn, &query).unwrap().iter().map(|t| t.id).collect::<Vec<_>>()
+        };
+        assert_eq!(due(display), vec![a]);
+
+        // Moving the display zone west keeps the floating one at 23:59 local
+        set_setting(&conn, "timezone", "America/New_York").unwrap();
+        assert_eq!(refresh_floating_limits(&conn).unwrap(), 1);
+        assert_eq!(refresh_floating_limits(&conn).unwrap(), 0);
+        let display = display_zone(&conn).unwrap();
+        assert_eq!(due(display), vec![b, a]);
+        assert_eq!(get_task(&conn, a).unwrap().unwrap().limit.unwrap().local(display), naive("2026-03-05 23:59"));
+
+        assert_eq!(get_settings(&conn).unwrap(), vec![("timezone".to_string(), "America/New_York".to_string())]);
+        assert!(unset_setting(&conn, "timezone").unwrap());
+        assert_eq!(display_zone(&conn).unwrap(), DisplayZone::Local);
+    }
+
+    #[test]
+    fn test_all_day_deadlines() {
+        let conn = test_conn();
+        let naive = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        let day = NaiveDate::from_ymd_opt(2026, 3, 5).unwrap();
+        let display = DisplayZone::Local;
+
+        let mut t = Task::new("all day");
+        t.limit = Some(Deadline::Day(day));
+        let all_day = add_task(&conn, &t).unwrap();
+        t.title = "late".into();
+        t.limit = Some(Deadline::At(display.resolve(naive("2026-03-05 23:59")), None));
+        let late = add_task(&conn, &t).unwrap();
+        t.title = "next day".into();
+        t.limit = Some(Deadline::Floating(naive("2026-03-06 00:00")));
+        let next_day = add_task(&conn, &t).unwrap();
+
+        // Due by the end of its day: after every timed deadline of that day
+        let ids: Vec<i64> = get_tasks(&conn).unwrap().iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![late, all_day, next_day]);
+
+        let stored = get_task(&conn, all_day).unwrap().unwrap().limit.unwrap();
+        assert_eq!(stored, Deadline::Day(day));
+        assert_eq!(stored.format(display, "%Y-%m-%d"), "2026-03-05");
+        assert_eq!(stored.rezone(DeadlineZone::parse("Asia/Tokyo").unwrap(), display), stored);
+
+        let until = |date| TaskQuery::new().due_before(utils::parse_full_date_time(date, "", display));
+        assert_eq!(query_tasks(&conn, &until("2026/03/05")).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_deadline_moved_to() {
+        let naive = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        let day = NaiveDate::from_ymd_opt(2026, 3, 20).unwrap();
+        let display = DisplayZone::Local;
+        let tokyo = DeadlineZone::parse("Asia/Tokyo").unwrap();
+
+        let all_day = Deadline::Day(NaiveDate::from_ymd_opt(2026, 3, 5).unwrap());
+        assert_eq!(all_day.moved_to(day, display), Deadline::Day(day));
+        let floating = Deadline::Floating(naive("2026-03-05 09:30"));
+        assert_eq!(floating.moved_to(day, display), Deadline::Floating(naive("2026-03-20 09:30")));
+
+        // Keeps the wall time in the deadline's own zone
+        let zoned = Deadline::Floating(naive("2026-03-05 01:00")).rezone(tokyo, display);
+        let moved = zoned.moved_to(day, display);
+        assert_eq!(moved.own_local(display), naive("2026-03-20 01:00"));
+        assert_eq!(moved.zone_label(), zoned.zone_label());
+    }
+
+    #[test]
+    fn test_workflow_states() {
+        let conn = test_conn();
+        let workflow = workflow(&conn).unwrap();
+        assert_eq!(workflow, Workflow::default());
+
+        let mut t = Task::new("review me");
+        workflow.set_column(&mut t, 2);
+        assert_eq!((t.is_done, t.state.as_deref()), (false, Some("review")));
+        let id = add_task(&conn, &t).unwrap();
+        let mut stored = get_task(&conn, id).unwrap().unwrap();
+        assert_eq!(workflow.state_name(&stored), "review");
+
+        // Moving to the last column completes the task
+        workflow.set_column(&mut stored, 3);
+        update_task(&conn, &stored).unwrap();
+        let done = get_task(&conn, id).unwrap().unwrap();
+        assert!(done.is_done && done.state.is_none());
+
+        // Toggling done from the CLI forgets the intermediate state
+        let mut t = get_task(&conn, id).unwrap().unwrap();
+        assert!(workflow.set_state(&mut t, "In-Progress"));
+        t.is_done = true;
+        update_task(&conn, &t).unwrap();
+        t = get_task(&conn, id).unwrap().unwrap();
+        t.is_done = false;
+        assert_eq!(workflow.state_name(&t), "todo");
+
+        // States dropped from the configuration fall back to the first one
+        set_setting(&conn, "workflow", "backlog,doing,done").unwrap();
+        let workflow = super::workflow(&conn).unwrap();
+        t.state = Some("review".into());
+        assert_eq!(workflow.state_name(&t), "backlog");
+        assert!(!workflow.set_state(&mut t, "review"));
+        assert!(Workflow::parse("todo").is_none());
+        assert!(Workflow::parse("todo,done,todo").is_none());
+    }
+
+    #[test]
+    fn test_scheduled_and_wait_dates() {
+        let conn = test_conn();
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d);
+        let today = day(4).unwrap();
+
+        let mut t = Task::new("planned");
+        t.scheduled = day(4);
+        let planned = add_task(&conn, &t).unwrap();
+        t.title = "overdue plan".into();
+        t.scheduled = day(1);
+        let overdue = add_task(&conn, &t).unwrap();
+        t.title = "later".into();
+        t.scheduled = day(9);
+        t.wait_until = day(8);
+        let later = add_task(&conn, &t).unwrap();
+        let plain = add_task(&conn, &Task::new("plain")).unwrap();
+
+        let stored = get_task(&conn, later).unwrap().unwrap();
+        assert_eq!((stored.scheduled, stored.wait_until), (day(9), day(8)));
+        assert!(stored.is_waiting(today));
+        assert!(!stored.is_waiting(day(8).unwrap()));
+
+        let ids = |query: TaskQuery| query_tasks(&conn, &query).unwrap().iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(ids(TaskQuery::new().actionable_on(Some(today))), vec![planned, overdue, plain]);
+        assert_eq!(ids(TaskQuery::new().scheduled_by(Some(today))), vec![planned, overdue]);
+        assert_eq!(ids(TaskQuery::new().actionable_on(day(8))).len(), 4);
+    }
+}
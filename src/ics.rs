@@ -0,0 +1,121 @@
+use chrono::{DateTime, Utc};
+
+use crate::db::{Deadline, Priority, Task};
+
+/// Renders tasks as an iCalendar (RFC 5545) calendar of to-dos.
+///
+/// Limits become `DUE`: all-day limits as `DATE` values, floating ones as local
+/// times without a zone, and everything else in UTC (the zone a task was entered
+/// in would need a `VTIMEZONE` definition, which calendar apps supply themselves).
+pub fn export(tasks: &[Task], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//sakd//sakd//EN".to_string(),
+    ];
+
+    for task in tasks {
+        lines.push("BEGIN:VTODO".to_string());
+        lines.push(format!("UID:sakd-{}@sakd", task.id));
+        lines.push(format!("DTSTAMP:{}", now.format("%Y%m%dT%H%M%SZ")));
+        lines.push(format!("SUMMARY:{}", escape_text(&task.title)));
+        if let Some(description) = &task.description {
+            lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        match task.limit {
+            Some(Deadline::Day(date)) => lines.push(format!("DUE;VALUE=DATE:{}", date.format("%Y%m%d"))),
+            Some(Deadline::Floating(naive)) => lines.push(format!("DUE:{}", naive.format("%Y%m%dT%H%M%S"))),
+            Some(Deadline::At(at, _)) => lines.push(format!("DUE:{}", at.format("%Y%m%dT%H%M%SZ"))),
+            None => {}
+        }
+        if !task.tags.is_empty() {
+            let tags: Vec<String> = task.tags.iter().map(|t| escape_text(t)).collect();
+            lines.push(format!("CATEGORIES:{}", tags.join(",")));
+        }
+        // 1 is the highest priority, 9 the lowest
+        match task.priority {
+            Priority::High => lines.push("PRIORITY:1".to_string()),
+            Priority::Medium => lines.push("PRIORITY:5".to_string()),
+            Priority::Low => lines.push("PRIORITY:9".to_string()),
+            Priority::None => {}
+        }
+        lines.push(format!("STATUS:{}", if task.is_done { "COMPLETED" } else { "NEEDS-ACTION" }));
+        lines.push("END:VTODO".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|l| fold_line(l)).collect::<Vec<_>>().join("\r\n") + "\r\n"
+}
+
+/// Escapes `\`, `;`, `,` and newlines in a TEXT value.
+fn escape_text(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Splits a content line into lines of at most 75 octets, continuing with a
+/// leading space, without cutting a UTF-8 character in half.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone};
+
+    #[test]
+    fn test_export_due_values() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 9, 0, 0).unwrap();
+        let day = NaiveDate::from_ymd_opt(2026, 3, 5).unwrap();
+
+        let mut all_day = Task::new("Pay rent; bills, etc.");
+        all_day.id = 1;
+        all_day.limit = Some(Deadline::Day(day));
+        all_day.tags = vec!["home".into(), "money".into()];
+        all_day.priority = Priority::High;
+
+        let mut floating = Task::new("Call");
+        floating.id = 2;
+        floating.limit = Some(Deadline::Floating(day.and_hms_opt(9, 30, 0).unwrap()));
+        floating.is_done = true;
+
+        let mut zoned = Task::new("Meeting");
+        zoned.id = 3;
+        zoned.limit = Some(Deadline::At(Utc.with_ymd_and_hms(2026, 3, 5, 6, 0, 0).unwrap(), Some(chrono_tz::Asia::Tokyo)));
+        zoned.description = Some("line 1\nline 2".into());
+
+        let ics = export(&[all_day, floating, zoned], now);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VTODO").count(), 3);
+        assert!(ics.contains("SUMMARY:Pay rent\\; bills\\, etc.\r\n"));
+        assert!(ics.contains("DUE;VALUE=DATE:20260305\r\n"));
+        assert!(ics.contains("CATEGORIES:home,money\r\nPRIORITY:1\r\n"));
+        assert!(ics.contains("DUE:20260305T093000\r\nSTATUS:COMPLETED\r\n"));
+        assert!(ics.contains("DUE:20260305T060000Z\r\n"));
+        assert!(ics.contains("DESCRIPTION:line 1\\nline 2\r\n"));
+    }
+
+    #[test]
+    fn test_fold_line() {
+        let line = format!("SUMMARY:{}", "締め切り".repeat(10));
+        let folded = fold_line(&line);
+        assert!(folded.split("\r\n").all(|l| l.len() <= 75));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+        assert_eq!(fold_line("SUMMARY:short"), "SUMMARY:short");
+    }
+}
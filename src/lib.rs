@@ -1,4 +1,9 @@
 pub mod db;
 pub mod cli;
+pub mod ics;
+pub mod pomodoro;
+pub mod report;
+pub mod score;
+pub mod stats;
 pub mod tui;
 pub mod utils;
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Duration, Utc};
+
+/// Default length of a work phase, in minutes.
+pub const WORK_MINUTES: i64 = 25;
+/// Default length of a break, in minutes.
+pub const BREAK_MINUTES: i64 = 5;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Phase {
+    Work,
+    Break,
+}
+
+/// A pomodoro timer running on one task: work, break, work...
+#[derive(Clone, PartialEq, Debug)]
+pub struct Pomodoro {
+    pub task_id: i64,
+    pub phase: Phase,
+    /// When the current phase began.
+    pub started: DateTime<Utc>,
+    pub work: Duration,
+    pub rest: Duration,
+}
+
+impl Pomodoro {
+    /// Starts with a work phase at `now`.
+    pub fn start(task_id: i64, (work, rest): (Duration, Duration), now: DateTime<Utc>) -> Self {
+        Pomodoro { task_id, phase: Phase::Work, started: now, work, rest }
+    }
+
+    /// Length of the current phase.
+    pub fn length(&self) -> Duration {
+        match self.phase {
+            Phase::Work => self.work,
+            Phase::Break => self.rest,
+        }
+    }
+
+    /// Time left in the current phase, never negative.
+    pub fn remaining(&self, now: DateTime<Utc>) -> Duration {
+        (self.started + self.length() - now).max(Duration::zero())
+    }
+
+    /// Share of the current phase that has passed, from 0.0 to 1.0.
+    pub fn progress(&self, now: DateTime<Utc>) -> f64 {
+        let length = self.length().num_seconds().max(1) as f64;
+        (1.0 - self.remaining(now).num_seconds() as f64 / length).clamp(0.0, 1.0)
+    }
+
+    /// Switches to the other phase once the current one is over, and returns
+    /// the phase that ended. The next phase starts at `now`, so time spent
+    /// away from the timer isn't counted as pomodoros.
+    pub fn advance(&mut self, now: DateTime<Utc>) -> Option<Phase> {
+        if self.remaining(now) > Duration::zero() {
+            return None;
+        }
+        let ended = self.phase;
+        self.phase = match ended {
+            Phase::Work => Phase::Break,
+            Phase::Break => Phase::Work,
+        };
+        self.started = now;
+        Some(ended)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_pomodoro_phases() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 5, 9, 0, 0).unwrap();
+        let mut p = Pomodoro::start(7, (Duration::minutes(25), Duration::minutes(5)), now);
+
+        let later = now + Duration::minutes(10);
+        assert_eq!(p.advance(later), None);
+        assert_eq!(p.remaining(later), Duration::minutes(15));
+        assert_eq!(p.progress(later), 0.4);
+
+        // Coming back late: the break starts when the timer notices
+        let late = now + Duration::minutes(40);
+        assert_eq!(p.advance(late), Some(Phase::Work));
+        assert_eq!((p.phase, p.started), (Phase::Break, late));
+        assert_eq!(p.remaining(late), Duration::minutes(5));
+
+        assert_eq!(p.advance(late + Duration::minutes(5)), Some(Phase::Break));
+        assert_eq!(p.phase, Phase::Work);
+    }
+}
@@ -0,0 +1,180 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::HashMap;
+
+use crate::db::{Task, TimeEntry};
+use crate::utils::DisplayZone;
+
+/// How `sakd report` groups its rows.
+#[derive(Clone, Copy, PartialEq, Debug, clap::ValueEnum)]
+pub enum Group {
+    Tag,
+    Day,
+    Task,
+}
+
+/// Time tracked and tasks completed under one tag, day or task.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Row {
+    pub label: String,
+    pub tracked: Duration,
+    pub completed: usize,
+}
+
+/// Label of the tag row for tasks without tags.
+pub const NO_TAG: &str = "(no tag)";
+
+/// Sums up the days `from..=to` in `zone`. Entries are cut at the edges of the
+/// period (and, grouped by day, at midnight), running ones count up to `now`.
+/// `tasks` holds the tasks of the entries and those completed in the period;
+/// only the latter count as completed.
+pub fn build(
+    group: Group,
+    entries: &[TimeEntry],
+    tasks: &HashMap<i64, Task>,
+    (from, to): (NaiveDate, NaiveDate),
+    zone: DisplayZone,
+    now: DateTime<Utc>,
+) -> Vec<Row> {
+    let day_start = |day: NaiveDate| zone.resolve(day.and_hms_opt(0, 0, 0).unwrap());
+    let (start, end) = (day_start(from), day_start(to + Duration::days(1)));
+
+    let mut rows: HashMap<String, Row> = HashMap::new();
+    let mut add = |label: String, tracked: Duration, completed: usize| {
+        let row = rows.entry(label.clone()).or_insert(Row { label, tracked: Duration::zero(), completed: 0 });
+        row.tracked += tracked;
+        row.completed += completed;
+    };
+    let labels = |task_id: i64| -> Vec<String> {
+        match (group, tasks.get(&task_id)) {
+            (Group::Tag, Some(task)) if !task.tags.is_empty() => task.tags.clone(),
+            (Group::Tag, _) => vec![NO_TAG.to_string()],
+            (Group::Task, Some(task)) => vec![format!("{} {}", task.id, task.title)],
+            (Group::Task, None) => vec![task_id.to_string()],
+            (Group::Day, _) => Vec::new(),
+        }
+    };
+
+    for entry in entries {
+        let entry_start = entry.started.max(start);
+        let entry_end = entry.stopped.unwrap_or(now).min(end);
+        if entry_start >= entry_end {
+            continue;
+        }
+        if group == Group::Day {
+            let mut day = zone.naive(entry_start).date();
+            while day_start(day) < entry_end {
+                let next = day_start(day + Duration::days(1));
+                let part = entry_end.min(next) - entry_start.max(day_start(day));
+                add(day.format("%Y-%m-%d").to_string(), part, 0);
+                day += Duration::days(1);
+            }
+        } else {
+            for label in labels(entry.task_id) {
+                add(label, entry_end - entry_start, 0);
+            }
+        }
+    }
+
+    for task in tasks.values() {
+        match task.completed {
+            Some(at) if start <= at && at < end => {
+                if group == Group::Day {
+                    add(zone.naive(at).date().format("%Y-%m-%d").to_string(), Duration::zero(), 1);
+                } else {
+                    for label in labels(task.id) {
+                        add(label, Duration::zero(), 1);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut rows: Vec<Row> = rows.into_values().collect();
+    match group {
+        Group::Day => rows.sort_by(|a, b| a.label.cmp(&b.label)),
+        _ => rows.sort_by(|a, b| b.tracked.cmp(&a.tracked).then_with(|| b.completed.cmp(&a.completed)).then_with(|| a.label.cmp(&b.label))),
+    }
+    rows
+}
+
+/// Hours with two decimals, as timesheets want them.
+pub fn hours(d: Duration) -> String {
+    format!("{:.2}", d.num_seconds() as f64 / 3600.0)
+}
+
+/// `rows` as CSV with a header line: label, minutes, hours, completed.
+pub fn to_csv(group: Group, rows: &[Row]) -> String {
+    let header = match group {
+        Group::Tag => "tag",
+        Group::Day => "day",
+        Group::Task => "task",
+    };
+    let mut csv = format!("{},minutes,hours,completed\n", header);
+    for row in rows {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            csv_field(&row.label),
+            row.tracked.num_minutes(),
+            hours(row.tracked),
+            row.completed
+        ));
+    }
+    csv
+}
+
+/// Quotes a CSV field when it contains a comma, quote or line break.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_build_report() {
+        let zone = DisplayZone::Named(chrono_tz::UTC);
+        let at = |d, h, m| Utc.with_ymd_and_hms(2026, 3, d, h, m, 0).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+
+        let mut spec = Task::new("Write spec, v2");
+        spec.id = 1;
+        spec.tags = vec!["work".into(), "docs".into()];
+        spec.completed = Some(at(5, 18, 0));
+        let mut chores = Task::new("Chores");
+        chores.id = 2;
+        let tasks = HashMap::from([(1, spec), (2, chores)]);
+
+        let entry = |id, task_id, start, stop| TimeEntry { id, task_id, started: start, stopped: stop };
+        let entries = [
+            // Starts before the period: only the part inside counts
+            entry(1, 1, at(3, 23, 0), Some(at(4, 1, 30))),
+            // Runs over midnight
+            entry(2, 2, at(5, 23, 30), Some(at(6, 0, 15))),
+            // Still running
+            entry(3, 1, at(6, 9, 0), None),
+        ];
+        let period = (day(4), day(6));
+        let now = at(6, 10, 0);
+
+        let by_day = build(Group::Day, &entries, &tasks, period, zone, now);
+        let summary: Vec<(&str, i64, usize)> = by_day.iter().map(|r| (r.label.as_str(), r.tracked.num_minutes(), r.completed)).collect();
+        assert_eq!(summary, vec![("2026-03-04", 90, 0), ("2026-03-05", 30, 1), ("2026-03-06", 75, 0)]);
+
+        let by_tag = build(Group::Tag, &entries, &tasks, period, zone, now);
+        let summary: Vec<(&str, i64, usize)> = by_tag.iter().map(|r| (r.label.as_str(), r.tracked.num_minutes(), r.completed)).collect();
+        assert_eq!(summary, vec![("docs", 150, 1), ("work", 150, 1), (NO_TAG, 45, 0)]);
+
+        let by_task = build(Group::Task, &entries, &tasks, period, zone, now);
+        assert_eq!(
+            to_csv(Group::Task, &by_task),
+            "task,minutes,hours,completed\n\"1 Write spec, v2\",150,2.50,1\n2 Chores,45,0.75,0\n"
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+use crate::db::{Priority, Task};
+use crate::utils::{self, DisplayZone};
+
+/// Estimates up to this long count as quick wins.
+const QUICK_WIN_MINUTES: i64 = 30;
+
+/// One part of a task's score and why it applies.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Factor {
+    pub name: &'static str,
+    pub points: i64,
+    pub reason: String,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Score {
+    pub task_id: i64,
+    pub total: i64,
+    /// The factors that added or took away points, largest first.
+    pub factors: Vec<Factor>,
+}
+
+/// What scoring needs to know beyond the task itself.
+pub struct Context {
+    pub now: DateTime<Utc>,
+    pub zone: DisplayZone,
+    /// Unfinished dependencies of each task, see `db::open_dependencies`.
+    pub open_dependencies: HashMap<i64, Vec<i64>>,
+}
+
+impl Context {
+    /// Number of tasks waiting on `id`.
+    fn blocking(&self, id: i64) -> usize {
+        self.open_dependencies.values().filter(|deps| deps.contains(&id)).count()
+    }
+}
+
+/// Scores how much a task should be done now. Close and missed deadlines
+/// weigh the most, more so when the estimate doesn't fit in the time left,
+/// then priority, being planned for today, unblocking other tasks, being a
+/// quick win and having been open for long. Tasks still waiting on others
+/// drop below everything that can be started.
+pub fn score(task: &Task, ctx: &Context) -> Score {
+    let mut factors = Vec::new();
+    let mut add = |name, points, reason: String| {
+        if points != 0 {
+            factors.push(Factor { name, points, reason });
+        }
+    };
+
+    if let Some(limit) = task.limit {
+        let left = limit.instant(ctx.zone) - ctx.now;
+        let (points, reason) = if left < Duration::zero() {
+            (100, format!("overdue by {}", format_span(-left)))
+        } else {
+            let points = match left.num_hours() {
+                0..24 => 80,
+                24..72 => 50,
+                72..168 => 25,
+                168..720 => 10,
+                _ => 0,
+            };
+            (points, format!("due in {}", format_span(left)))
+        };
+        add("deadline", points, reason);
+
+        if let Some(estimate) = task.estimate.filter(|e| left >= Duration::zero() && *e > left) {
+            add("estimate", 20, format!("needs {} of work before the deadline", utils::format_duration(estimate)));
+        }
+    }
+    if let Some(estimate) = task.estimate.filter(|e| *e <= Duration::minutes(QUICK_WIN_MINUTES)) {
+        add("estimate", 10, format!("quick win ({})", utils::format_duration(estimate)));
+    }
+
+    let points = match task.priority {
+        Priority::High => 30,
+        Priority::Medium => 20,
+        Priority::Low => 10,
+        Priority::None => 0,
+    };
+    add("priority", points, format!("{:?} priority", task.priority).to_lowercase());
+
+    let today = ctx.zone.naive(ctx.now).date();
+    if let Some(scheduled) = task.scheduled.filter(|&d| d <= today) {
+        let reason = match (today - scheduled).num_days() {
+            0 => "scheduled for today".to_string(),
+            days => format!("scheduled {} day(s) ago", days),
+        };
+        add("schedule", 20, reason);
+    }
+
+    match ctx.open_dependencies.get(&task.id) {
+        Some(deps) => {
+            let ids: Vec<String> = deps.iter().map(|id| format!("#{}", id)).collect();
+            add("dependencies", -200, format!("waiting on {}", ids.join(", ")));
+        }
+        None => {
+            let blocking = ctx.blocking(task.id);
+            add("dependencies", 15 * blocking.min(3) as i64, format!("unblocks {} task(s)", blocking));
+        }
+    }
+
+    if let Some(created) = task.created {
+        let days = (ctx.now - created).num_days();
+        add("age", (days / 7).min(10), format!("open for {} days", days));
+    }
+
+    factors.sort_by_key(|f| -f.points.abs());
+    Score { task_id: task.id, total: factors.iter().map(|f| f.points).sum(), factors }
+}
+
+/// Scores the open tasks that aren't waiting, best first. Ties keep the order
+/// of `tasks`.
+pub fn rank(tasks: &[Task], ctx: &Context) -> Vec<Score> {
+    let today = ctx.zone.naive(ctx.now).date();
+    let mut scores: Vec<Score> = tasks.iter()
+        .filter(|t| !t.is_done && !t.is_waiting(today))
+        .map(|t| score(t, ctx))
+        .collect();
+    scores.sort_by_key(|s| -s.total);
+    scores
+}
+
+/// "3 days", "5 hours" or "40 minutes".
+fn format_span(span: Duration) -> String {
+    if span.num_days() > 0 {
+        format!("{} day(s)", span.num_days())
+    } else if span.num_hours() > 0 {
+        format!("{} hour(s)", span.num_hours())
+    } else {
+        format!("{} minute(s)", span.num_minutes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Deadline;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_rank_explains_score() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 5, 9, 0, 0).unwrap();
+        let zone = DisplayZone::Named(chrono_tz::UTC);
+
+        let mut due_soon = Task::new("due soon");
+        due_soon.id = 1;
+        due_soon.limit = Some(Deadline::At(now + Duration::hours(5), None));
+        let mut urgent = Task::new("high priority, unblocks #3");
+        urgent.id = 2;
+        urgent.priority = Priority::High;
+        urgent.created = Some(now - Duration::days(21));
+        due_soon.estimate = Some(Duration::hours(6));
+        let mut blocked = Task::new("blocked but overdue");
+        blocked.id = 3;
+        blocked.limit = Some(Deadline::At(now - Duration::days(2), None));
+        let mut done = Task::new("done");
+        done.id = 4;
+        done.is_done = true;
+
+        let ctx = Context { now, zone, open_dependencies: HashMap::from([(3, vec![2])]) };
+        let scores = rank(&[due_soon, urgent, blocked, done], &ctx);
+
+        assert_eq!(scores.iter().map(|s| (s.task_id, s.total)).collect::<Vec<_>>(), vec![(1, 100), (2, 48), (3, -100)]);
+        let reasons: Vec<(&str, i64, &str)> = scores[1].factors.iter().map(|f| (f.name, f.points, f.reason.as_str())).collect();
+        assert_eq!(reasons, vec![
+            ("priority", 30, "high priority"),
+            ("dependencies", 15, "unblocks 1 task(s)"),
+            ("age", 3, "open for 21 days"),
+        ]);
+        assert_eq!(scores[0].factors[1].reason, "needs 6h 00m of work before the deadline");
+        assert_eq!(scores[2].factors[0].reason, "waiting on #2");
+        assert_eq!(scores[2].factors[1].reason, "overdue by 2 day(s)");
+    }
+}
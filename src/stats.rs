@@ -0,0 +1,301 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::db::Task;
+use crate::utils::{self, DisplayZone};
+
+/// Number of weeks `build` counts completions for.
+pub const WEEKS: usize = 8;
+
+/// Share of a tag's tasks that are done.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TagRate {
+    pub tag: String,
+    pub done: usize,
+    pub total: usize,
+}
+
+impl TagRate {
+    pub fn percent(&self) -> u64 {
+        (self.done * 100 / self.total.max(1)) as u64
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Stats {
+    /// Tasks completed on each of the last days, oldest first.
+    pub daily: Vec<(NaiveDate, usize)>,
+    /// Tasks completed in each of the last `WEEKS` weeks, by the Monday they
+    /// start on, oldest first.
+    pub weekly: Vec<(NaiveDate, usize)>,
+    pub open: usize,
+    pub overdue: usize,
+    pub done: usize,
+    /// Average time from creation to completion.
+    pub lead_time: Option<Duration>,
+    /// Tags by completion rate, best first.
+    pub tags: Vec<TagRate>,
+    /// Days in a row with a completion, up to today (or yesterday, while
+    /// today still has time).
+    pub streak: usize,
+    pub best_streak: usize,
+}
+
+/// Statistics over `tasks` for the `days` days up to `now` in `zone`.
+/// Completions and lead times need `completed`/`created`, so tasks from before
+/// those were recorded only count towards the totals.
+pub fn build(tasks: &[Task], days: usize, zone: DisplayZone, now: DateTime<Utc>) -> Stats {
+    let today = zone.naive(now).date();
+    let mut per_day: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    let mut lead_times = Vec::new();
+    let mut tags: BTreeMap<&str, TagRate> = BTreeMap::new();
+
+    for task in tasks {
+        if let Some(completed) = task.completed.filter(|_| task.is_done) {
+            *per_day.entry(zone.naive(completed).date()).or_default() += 1;
+            if let Some(created) = task.created {
+                lead_times.push(completed - created);
+            }
+        }
+        for tag in &task.tags {
+            let rate = tags.entry(tag).or_insert_with(|| TagRate { tag: tag.clone(), done: 0, total: 0 });
+            rate.total += 1;
+            rate.done += task.is_done as usize;
+        }
+    }
+    let count = |from: NaiveDate, to: NaiveDate| per_day.range(from..to).map(|(_, n)| n).sum();
+
+    let daily = (0..days as i64).rev()
+        .map(|ago| today - Duration::days(ago))
+        .map(|day| (day, count(day, day + Duration::days(1))))
+        .collect();
+    let this_week = utils::week_start(today);
+    let weekly = (0..WEEKS as i64).rev()
+        .map(|ago| this_week - Duration::weeks(ago))
+        .map(|week| (week, count(week, week + Duration::weeks(1))))
+        .collect();
+
+    let open: Vec<&Task> = tasks.iter().filter(|t| !t.is_done).collect();
+    let overdue = open.iter().filter(|t| t.limit.is_some_and(|l| l.instant(zone) < now)).count();
+    let lead_time = (!lead_times.is_empty())
+        .then(|| lead_times.iter().copied().sum::<Duration>() / lead_times.len() as i32);
+
+    let mut tags: Vec<TagRate> = tags.into_values().collect();
+    tags.sort_by(|a, b| b.percent().cmp(&a.percent()).then_with(|| b.total.cmp(&a.total)));
+
+    let (streak, best_streak) = streaks(&per_day.into_keys().collect(), today);
+    Stats {
+        daily,
+        weekly,
+        open: open.len(),
+        overdue,
+        done: tasks.len() - open.len(),
+        lead_time,
+        tags,
+        streak,
+        best_streak,
+    }
+}
+
+/// The current and the longest run of consecutive days in `days`.
+fn streaks(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> (usize, usize) {
+    let mut best = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for &day in days {
+        run = if previous == Some(day - Duration::days(1)) { run + 1 } else { 1 };
+        best = best.max(run);
+        previous = Some(day);
+    }
+
+    let mut day = if days.contains(&today) { today } else { today - Duration::days(1) };
+    let mut current = 0;
+    while days.contains(&day) {
+        current += 1;
+        day -= Duration::days(1);
+    }
+    (current, best)
+}
+
+/// "3.5 days" for long spans, "5h 20m" for shorter ones.
+pub fn format_lead_time(d: Duration) -> String {
+    if d >= Duration::days(1) {
+        format!("{:.1} days", d.num_minutes() as f64 / (24.0 * 60.0))
+    } else {
+        utils::format_duration(d)
+    }
+}
+
+/// Tasks open and completed at the end of a day.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BurndownPoint {
+    pub day: NaiveDate,
+    pub open: usize,
+    pub completed: usize,
+}
+
+/// Open and completed tasks at the end of each day `from..=to`, replayed from
+/// creation and completion times. Tasks without a creation time count from the
+/// start; done tasks without a completion time count as completed from it too.
+pub fn burndown(tasks: &[Task], (from, to): (NaiveDate, NaiveDate), zone: DisplayZone) -> Vec<BurndownPoint> {
+    from.iter_days()
+        .take_while(|day| *day <= to)
+        .map(|day| {
+            let end = zone.resolve((day + Duration::days(1)).and_hms_opt(0, 0, 0).unwrap());
+            let existing = tasks.iter().filter(|t| t.created.is_none_or(|c| c < end));
+            let (completed, open): (Vec<&Task>, Vec<&Task>) =
+                existing.partition(|t| t.is_done && t.completed.is_none_or(|c| c < end));
+            BurndownPoint { day, open: open.len(), completed: completed.len() }
+        })
+        .collect()
+}
+
+/// Days a burndown covers when no first day is given.
+pub const BURNDOWN_DAYS: i64 = 30;
+
+/// First day for a burndown of `tasks` up to `to`: the day the oldest was
+/// created, but no more than `BURNDOWN_DAYS` days back.
+pub fn burndown_start(tasks: &[Task], zone: DisplayZone, to: NaiveDate) -> NaiveDate {
+    let earliest = to - Duration::days(BURNDOWN_DAYS - 1);
+    tasks.iter()
+        .filter_map(|t| t.created)
+        .min()
+        .map_or(to, |c| zone.naive(c).date().clamp(earliest, to))
+}
+
+/// The ideal line: from the first day's open tasks straight down to zero on
+/// the last day.
+pub fn ideal(points: &[BurndownPoint]) -> Vec<f64> {
+    let start = points.first().map_or(0, |p| p.open) as f64;
+    let steps = points.len().saturating_sub(1).max(1) as f64;
+    (0..points.len()).map(|i| start * (1.0 - i as f64 / steps)).collect()
+}
+
+/// A burndown as text, `height` rows high: `█` for open tasks with the
+/// completed ones stacked on top as `░`, and `·` for the ideal line drawn
+/// over both. Each day is `width` characters wide.
+pub fn burndown_chart(points: &[BurndownPoint], height: usize, width: usize) -> Vec<String> {
+    let max = points.iter().map(|p| p.open + p.completed).max().unwrap_or(0).max(1);
+    let scale = |n: f64| (n * height as f64 / max as f64).round() as usize;
+    let ideal = ideal(points);
+
+    let mut lines = Vec::new();
+    for row in (1..=height).rev() {
+        let axis = match row {
+            _ if row == height => format!("{:>4} │", max),
+            _ if row == height.div_ceil(2) => format!("{:>4} │", (max * row + height / 2) / height),
+            _ => "     │".to_string(),
+        };
+        let cells: String = points.iter().zip(&ideal).map(|(p, ideal)| {
+            let cell = if scale(*ideal) == row {
+                '·'
+            } else if scale(p.open as f64) >= row {
+                '█'
+            } else if scale((p.open + p.completed) as f64) >= row {
+                '░'
+            } else {
+                ' '
+            };
+            cell.to_string().repeat(width)
+        }).collect();
+        lines.push(format!("{}{}", axis, cells.trim_end()));
+    }
+    lines.push(format!("{:>4} └{}", 0, "─".repeat(points.len() * width)));
+
+    if let (Some(first), Some(last)) = (points.first(), points.last()) {
+        let first_label = first.day.format("%m/%d").to_string();
+        let last_label = last.day.format("%m/%d").to_string();
+        let gap = (points.len() * width).saturating_sub(first_label.len() + last_label.len()).max(1);
+        if points.len() > 1 {
+            lines.push(format!("      {}{}{}", first_label, " ".repeat(gap), last_label));
+        } else {
+            lines.push(format!("      {}", first_label));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Deadline;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_build_stats() {
+        let zone = DisplayZone::Named(chrono_tz::UTC);
+        let now = Utc.with_ymd_and_hms(2026, 3, 11, 12, 0, 0).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+
+        let mut tasks = Vec::new();
+        // Completed on the 3rd, 4th, 5th, then the 9th and 10th
+        for (i, d) in [3, 4, 5, 9, 10, 10].into_iter().enumerate() {
+            let mut t = Task::new("done");
+            t.id = i as i64;
+            t.is_done = true;
+            t.created = Some(Utc.with_ymd_and_hms(2026, 3, d, 0, 0, 0).unwrap() - Duration::days(1));
+            t.completed = Some(Utc.with_ymd_and_hms(2026, 3, d, 0, 0, 0).unwrap());
+            t.tags = vec![if i < 2 { "work".into() } else { "home".into() }];
+            tasks.push(t);
+        }
+        let mut late = Task::new("late");
+        late.limit = Some(Deadline::Day(day(10)));
+        late.tags = vec!["work".into()];
+        tasks.push(late);
+        tasks.push(Task::new("open"));
+
+        let stats = build(&tasks, 3, zone, now);
+        assert_eq!(stats.daily, vec![(day(9), 1), (day(10), 2), (day(11), 0)]);
+        assert_eq!(stats.weekly.len(), WEEKS);
+        assert_eq!(stats.weekly[WEEKS - 2..], [(day(2), 3), (day(9), 3)]);
+        assert_eq!((stats.open, stats.overdue, stats.done), (2, 1, 6));
+        assert_eq!(stats.lead_time, Some(Duration::days(1)));
+        assert_eq!(format_lead_time(Duration::hours(36)), "1.5 days");
+        // Nothing yet today, so the streak still runs from yesterday
+        assert_eq!((stats.streak, stats.best_streak), (2, 3));
+
+        let rates: Vec<(&str, usize, usize)> = stats.tags.iter().map(|r| (r.tag.as_str(), r.done, r.total)).collect();
+        assert_eq!(rates, vec![("home", 4, 4), ("work", 2, 3)]);
+    }
+
+    #[test]
+    fn test_burndown() {
+        let zone = DisplayZone::Named(chrono_tz::UTC);
+        let at = |d, h| Utc.with_ymd_and_hms(2026, 3, d, h, 0, 0).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        let task = |created: Option<DateTime<Utc>>, completed: Option<DateTime<Utc>>| {
+            let mut t = Task::new("t");
+            t.created = created;
+            t.is_done = completed.is_some();
+            t.completed = completed;
+            t
+        };
+        let tasks = [
+            task(Some(at(2, 9)), Some(at(3, 17))),
+            task(Some(at(2, 9)), Some(at(5, 10))),
+            task(Some(at(2, 9)), None),
+            // Added mid-sprint
+            task(Some(at(4, 12)), None),
+            task(None, None),
+        ];
+
+        assert_eq!(burndown_start(&tasks, zone, day(10)), day(2));
+        let april = |d| NaiveDate::from_ymd_opt(2026, 4, d).unwrap();
+        assert_eq!(burndown_start(&tasks, zone, april(30)), april(1));
+        let points = burndown(&tasks, (day(2), day(5)), zone);
+        let summary: Vec<(usize, usize)> = points.iter().map(|p| (p.open, p.completed)).collect();
+        assert_eq!(summary, vec![(4, 0), (3, 1), (4, 1), (3, 2)]);
+        let thirds: Vec<f64> = ideal(&points).iter().map(|v| (v * 3.0).round()).collect();
+        assert_eq!(thirds, vec![12.0, 8.0, 4.0, 0.0]);
+
+        assert_eq!(burndown_chart(&points, 5, 2), vec![
+            "   5 │    ░░░░",
+            "     │··░░██░░",
+            "   3 │██··████",
+            "     │████████",
+            "     │████··██",
+            "   0 └────────",
+            "      03/02 03/05",
+        ]);
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, Utc};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
     execute,
@@ -8,18 +9,42 @@ use ratatui::{
     backend::{Backend, CrosstermBackend},
     layout::{Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    symbols,
+    text::{Line, Span},
+    widgets::{
+        Axis, Bar, BarChart, BarGroup, Block, Borders, Chart, Dataset, Gauge, GraphType, List, ListItem, ListState,
+        Paragraph, Sparkline,
+    },
     Frame, Terminal,
 };
 use rusqlite::Connection;
-use std::io;
+use std::collections::HashMap;
+use std::io::{self, Write};
 
-use crate::db::{self, Task};
-use crate::utils;
+use crate::db::{self, Priority, TagMeta, Task, TaskQuery};
+use crate::pomodoro::{Phase, Pomodoro};
+use crate::score::{self, Score};
+use crate::stats::{self, BurndownPoint, Stats};
+use crate::utils::{self, DeadlineZone, DisplayZone, Workflow};
+
+/// Number of tasks fetched from the database at a time while scrolling.
+const PAGE_SIZE: usize = 200;
+
+/// Sort orders `o` cycles through, as `utils::parse_sort` specifications.
+const SORT_ORDERS: [&str; 6] = ["limit", "priority,limit", "-created", "title", "id", "manual"];
+
+/// Index of the manual order in `SORT_ORDERS`.
+fn manual_sort() -> usize {
+    SORT_ORDERS.iter().position(|s| *s == "manual").unwrap()
+}
+
+/// Days shown in the stats screen's daily chart.
+const STATS_DAYS: usize = 30;
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum PopupStep {
     Title,
+    Priority,
     Tags,
     Date,
     Time,
@@ -28,33 +53,37 @@ pub enum PopupStep {
 
 pub enum InputMode {
     Normal,
-    Adding(PopupStep),
+    /// One-line quick add, parsed by `utils::parse_quick_add`.
+    QuickAdding,
     Editing(i64, PopupStep),
     Deleting(i64),
+    /// The task being edited was changed by another session; see `App::pending_edit`.
+    Conflict(i64),
     FilteringTag,
+    BrowsingTags,
+    /// Moving around the calendar pane.
+    Calendar,
+    /// Kanban board with a column per workflow state.
+    Board,
+    /// Explaining why the task jumped to with `s` is the one to do now.
+    Suggesting(Score),
+    /// Statistics screen opened with `S`.
+    Stats(Stats),
+    /// Burndown of the tasks matching the tag filter, opened with `B`.
+    Burndown(Vec<BurndownPoint>),
     Helping,
 }
 
+#[derive(Default)]
 pub struct PopupData {
     pub title: String,
+    pub priority: String,
     pub tags: String,
     pub date: String,
     pub time: String,
     pub description: String,
 }
 
-impl Default for PopupData {
-    fn default() -> Self {
-        Self {
-            title: String::new(),
-            tags: String::new(),
-            date: String::new(),
-            time: String::new(),
-            description: String::new(),
-        }
-    }
-}
-
 pub struct App<'a> {
     pub tasks: Vec<Task>,
     pub filtered_tasks: Vec<Task>,
@@ -64,14 +93,54 @@ pub struct App<'a> {
     pub popup_data: PopupData,
     pub show_done: bool,
     pub tag_filter: Option<String>,
+    /// Whether the database holds more matching tasks than are loaded.
+    pub has_more: bool,
+    /// Last seen `data_version`, used to notice writes from other sessions.
+    pub data_version: i64,
+    /// The task as it was when the edit popup opened.
+    pub edit_base: Option<Task>,
+    /// Edited task waiting for the user to resolve a conflict.
+    pub pending_edit: Option<Task>,
+    /// Tag hierarchy shown in the tag browser.
+    pub tag_list: Vec<utils::TagNode>,
+    pub tag_state: ListState,
+    /// Tag colors and icons, keyed by tag name.
+    pub tag_metas: HashMap<String, TagMeta>,
+    /// Zone deadlines are shown and entered in.
+    pub zone: DisplayZone,
+    pub show_calendar: bool,
+    /// Day under the calendar cursor.
+    pub calendar_day: NaiveDate,
+    /// Number of listed tasks due on each day of the calendar's month.
+    pub calendar_counts: HashMap<NaiveDate, usize>,
+    /// Only tasks due on this day are listed.
+    pub day_filter: Option<NaiveDate>,
+    /// Index into `SORT_ORDERS`.
+    pub sort: usize,
+    /// Time being tracked, shown in the header.
+    pub timer: Option<db::TimeEntry>,
+    /// Title of the timed task, which needn't be loaded or listed.
+    pub timer_title: String,
+    /// Finished tracked time per task.
+    pub tracked: HashMap<i64, Duration>,
+    /// Pomodoro timer, shown in a panel at the bottom.
+    pub pomodoro: Option<Pomodoro>,
+    /// Title of the pomodoro's task, which needn't be loaded or listed.
+    pub pomodoro_title: String,
+    /// Finished pomodoros per task.
+    pub pomodoros: HashMap<i64, i64>,
+    pub workflow: Workflow,
+    /// Tasks of each workflow state, filled while the board is shown.
+    pub board: Vec<Vec<Task>>,
+    pub board_column: usize,
+    pub board_states: Vec<ListState>,
     pub conn: &'a Connection,
 }
 
 impl<'a> App<'a> {
     pub fn new(conn: &'a Connection) -> Result<Self> {
-        let tasks = db::get_tasks(conn)?;
         let mut app = App {
-            tasks,
+            tasks: Vec::new(),
             filtered_tasks: Vec::new(),
             state: ListState::default(),
             input_mode: InputMode::Normal,
@@ -79,9 +148,32 @@ impl<'a> App<'a> {
             popup_data: PopupData::default(),
             show_done: false,
             tag_filter: None,
+            has_more: false,
+            data_version: db::data_version(conn)?,
+            edit_base: None,
+            pending_edit: None,
+            tag_list: Vec::new(),
+            tag_state: ListState::default(),
+            tag_metas: HashMap::new(),
+            zone: DisplayZone::default(),
+            show_calendar: false,
+            calendar_day: Local::now().date_naive(),
+            calendar_counts: HashMap::new(),
+            day_filter: None,
+            sort: 0,
+            timer: None,
+            timer_title: String::new(),
+            tracked: HashMap::new(),
+            pomodoro: None,
+            pomodoro_title: String::new(),
+            pomodoros: HashMap::new(),
+            workflow: Workflow::default(),
+            board: Vec::new(),
+            board_column: 0,
+            board_states: Vec::new(),
             conn,
         };
-        app.update_filtered_tasks();
+        app.refresh_tasks()?;
         Ok(app)
     }
 
@@ -91,18 +183,16 @@ impl<'a> App<'a> {
             .and_then(|i| self.filtered_tasks.get(i))
             .map(|t| t.id);
 
+        let today = self.zone.today();
         let mut filtered: Vec<Task> = self.tasks.iter()
-            .filter(|t| self.show_done || !t.is_done)
-            .filter(|t| self.tag_filter.as_ref().map_or(true, |f| t.tags.iter().any(|tag| tag.to_lowercase().contains(&f.to_lowercase()))))
+            .filter(|t| self.show_done || (!t.is_done && !t.is_waiting(today)))
+            .filter(|t| self.tag_filter.as_ref().is_none_or(|f| t.tags.iter().any(|tag| utils::tag_matches(tag, f))))
+            .filter(|t| self.day_filter.is_none_or(|d| t.limit.is_some_and(|l| l.date(self.zone) == d)))
             .cloned()
             .collect();
 
-        // Default sort: is_done ASC, limit ASC
-        filtered.sort_by(|a, b| {
-            a.is_done.cmp(&b.is_done)
-                .then_with(|| a.limit.is_none().cmp(&b.limit.is_none()))
-                .then_with(|| a.limit.cmp(&b.limit))
-        });
+        let order = self.sort_keys();
+        filtered.sort_by(|a, b| db::compare_tasks(&order, a, b, self.zone));
         
         self.filtered_tasks = filtered;
 
@@ -124,15 +214,397 @@ impl<'a> App<'a> {
         }
     }
 
+    fn sort_keys(&self) -> Vec<db::SortKey> {
+        utils::parse_sort(SORT_ORDERS[self.sort]).unwrap()
+    }
+
+    /// Switches to the next sort order in `SORT_ORDERS`.
+    pub fn cycle_sort(&mut self) -> Result<()> {
+        self.sort = (self.sort + 1) % SORT_ORDERS.len();
+        self.refresh_tasks()
+    }
+
+    /// Starts tracking time on the selected task, or stops the timer if it is
+    /// already running on it.
+    pub fn toggle_timer(&mut self) -> Result<()> {
+        let Some(task) = self.state.selected().and_then(|i| self.filtered_tasks.get(i)) else {
+            return Ok(());
+        };
+        if self.timer.as_ref().is_some_and(|t| t.task_id == task.id) {
+            db::stop_timer(self.conn, Utc::now())?;
+        } else {
+            db::start_timer(self.conn, task.id, Utc::now())?;
+        }
+        self.refresh_tasks()
+    }
+
+    /// Starts a pomodoro on the selected task, or stops the one running on it.
+    pub fn toggle_pomodoro(&mut self) -> Result<()> {
+        let Some(task) = self.state.selected().and_then(|i| self.filtered_tasks.get(i)) else {
+            return Ok(());
+        };
+        if self.pomodoro.as_ref().is_some_and(|p| p.task_id == task.id) {
+            self.pomodoro = None;
+        } else {
+            self.pomodoro = Some(Pomodoro::start(task.id, db::pomodoro_lengths(self.conn)?, Utc::now()));
+            self.pomodoro_title = task.title.clone();
+        }
+        Ok(())
+    }
+
+    /// Moves the pomodoro to its next phase when the current one is over,
+    /// ringing the terminal bell and counting finished work phases.
+    pub fn tick_pomodoro(&mut self) -> Result<()> {
+        let now = Utc::now();
+        let Some(ended) = self.pomodoro.as_mut().and_then(|p| p.advance(now)) else {
+            return Ok(());
+        };
+        let mut stdout = io::stdout();
+        stdout.write_all(b"\x07")?;
+        stdout.flush()?;
+        if ended == Phase::Work
+            && let Some(task_id) = self.pomodoro.as_ref().map(|p| p.task_id) {
+            // The task may have been deleted since the last refresh
+            if self.task_title(task_id)?.is_none() {
+                self.pomodoro = None;
+                return Ok(());
+            }
+            db::add_pomodoro(self.conn, task_id, now)?;
+            self.pomodoros = db::pomodoro_counts(self.conn)?;
+        }
+        Ok(())
+    }
+
+    /// Everything tracked on a task, including the running timer.
+    fn tracked_time(&self, task_id: i64) -> Duration {
+        let running = self.timer.as_ref().filter(|t| t.task_id == task_id).map(|t| t.duration(Utc::now()));
+        self.tracked.get(&task_id).copied().unwrap_or_default() + running.unwrap_or_default()
+    }
+
+    /// Moves the selected task one place up or down in the manual order,
+    /// switching the list to that order first if needed.
+    pub fn move_selected(&mut self, down: bool) -> Result<()> {
+        if self.sort != manual_sort() {
+            self.sort = manual_sort();
+            self.refresh_tasks()?;
+        }
+        let Some(i) = self.state.selected() else {
+            return Ok(());
+        };
+        let neighbour = if down { i.checked_add(1) } else { i.checked_sub(1) };
+        if let (Some(task), Some(other)) = (self.filtered_tasks.get(i), neighbour.and_then(|n| self.filtered_tasks.get(n)))
+            && task.is_done == other.is_done {
+            db::move_task(self.conn, task.id, other.id, down)?;
+            self.refresh_tasks()?;
+        }
+        Ok(())
+    }
+
+    fn query(&self) -> TaskQuery {
+        let query = TaskQuery::new()
+            .order(self.sort_keys())
+            .tag(self.tag_filter.clone())
+            .done(if self.show_done { None } else { Some(false) })
+            .actionable_on(if self.show_done { None } else { Some(self.zone.today()) });
+        match self.day_filter {
+            Some(day) => query.due_after(Some(self.day_start(day))).due_before(Some(self.day_start(day + Duration::days(1)) - Duration::seconds(1))),
+            None => query,
+        }
+    }
+
+    fn day_start(&self, day: NaiveDate) -> DateTime<Utc> {
+        self.zone.resolve(day.and_hms_opt(0, 0, 0).unwrap())
+    }
+
+    /// Title of a task for the timer and pomodoro bars, read from the database
+    /// since the task may be filtered out or on a page not loaded yet. `None`
+    /// once the task is deleted.
+    fn task_title(&self, id: i64) -> Result<Option<String>> {
+        Ok(db::get_task(self.conn, id)?.map(|t| t.title))
+    }
+
+    /// Reloads every page loaded so far, keeping the scroll depth.
     pub fn refresh_tasks(&mut self) -> Result<()> {
-        self.tasks = db::get_tasks(self.conn)?;
+        let count = self.tasks.len().max(PAGE_SIZE);
+        self.tasks = db::query_tasks(self.conn, &self.query().limit(Some(count)))?;
+        self.has_more = self.tasks.len() == count;
+        self.tag_metas = db::get_tag_metas(self.conn)?;
+        self.zone = db::display_zone(self.conn)?;
+        self.workflow = db::workflow(self.conn)?;
+        self.timer = db::running_timer(self.conn)?;
+        self.timer_title = match self.timer.as_ref().map(|t| t.task_id) {
+            Some(id) => self.task_title(id)?.unwrap_or_default(),
+            None => String::new(),
+        };
+        self.tracked = db::tracked_totals(self.conn)?;
+        self.pomodoros = db::pomodoro_counts(self.conn)?;
+        if let Some(id) = self.pomodoro.as_ref().map(|p| p.task_id) {
+            match self.task_title(id)? {
+                Some(title) => self.pomodoro_title = title,
+                // A pomodoro stops with its task, whichever session deleted it
+                None => self.pomodoro = None,
+            }
+        }
         self.update_filtered_tasks();
+        self.refresh_calendar()?;
+        self.refresh_board()
+    }
+
+    /// Sorts the tasks matching the list's filters into board columns. Only the
+    /// most recent page of completed tasks is loaded.
+    fn refresh_board(&mut self) -> Result<()> {
+        if !matches!(self.input_mode, InputMode::Board) {
+            return Ok(());
+        }
+        let selected_id = self.selected_card().map(|t| t.id);
+
+        self.board = vec![Vec::new(); self.workflow.states.len()];
+        let open = db::query_tasks(self.conn, &self.query().done(Some(false)))?;
+        let done = db::query_tasks(self.conn, &self.query().done(Some(true)).limit(Some(PAGE_SIZE)))?;
+        for task in open.into_iter().chain(done) {
+            self.board[self.workflow.column(&task)].push(task);
+        }
+
+        self.board_column = self.board_column.min(self.board.len() - 1);
+        self.board_states.resize_with(self.board.len(), ListState::default);
+        for (column, state) in self.board.iter().zip(&mut self.board_states) {
+            let i = state.selected().unwrap_or(0);
+            state.select(if column.is_empty() { None } else { Some(i.min(column.len() - 1)) });
+        }
+        if let Some(id) = selected_id {
+            self.select_card(id);
+        }
         Ok(())
     }
 
-    pub fn toggle_done_visibility(&mut self) {
-        self.show_done = !self.show_done;
+    pub fn open_board(&mut self) -> Result<()> {
+        self.input_mode = InputMode::Board;
+        self.refresh_board()
+    }
+
+    fn selected_card(&self) -> Option<&Task> {
+        let selected = self.board_states.get(self.board_column)?.selected()?;
+        self.board.get(self.board_column)?.get(selected)
+    }
+
+    /// Focuses the column holding the task and selects it there.
+    fn select_card(&mut self, id: i64) {
+        for (n, column) in self.board.iter().enumerate() {
+            if let Some(i) = column.iter().position(|t| t.id == id) {
+                self.board_column = n;
+                self.board_states[n].select(Some(i));
+            }
+        }
+    }
+
+    pub fn focus_board_column(&mut self, delta: isize) {
+        self.board_column = self.board_column.saturating_add_signed(delta).min(self.board.len().saturating_sub(1));
+    }
+
+    pub fn select_next_card(&mut self, forward: bool) {
+        let len = self.board.get(self.board_column).map_or(0, Vec::len);
+        if len == 0 {
+            return;
+        }
+        let state = &mut self.board_states[self.board_column];
+        let i = state.selected().unwrap_or(0);
+        state.select(Some(if forward { (i + 1) % len } else { (i + len - 1) % len }));
+    }
+
+    /// Moves the selected task to the neighbouring workflow state; moving it
+    /// into the last one completes it.
+    pub fn move_card(&mut self, delta: isize) -> Result<()> {
+        let Some(mut task) = self.selected_card().cloned() else {
+            return Ok(());
+        };
+        let column = self.board_column.saturating_add_signed(delta).min(self.board.len() - 1);
+        if column == self.board_column {
+            return Ok(());
+        }
+        self.workflow.set_column(&mut task, column);
+        db::update_task(self.conn, &task)?;
+        self.refresh_tasks()?;
+        self.select_card(task.id);
+        Ok(())
+    }
+
+    /// Recounts the tasks due on each day of the calendar's month, with the
+    /// current filters except the day filter.
+    fn refresh_calendar(&mut self) -> Result<()> {
+        if !self.show_calendar {
+            return Ok(());
+        }
+        let first = self.calendar_day.with_day(1).unwrap();
+        let next_month = utils::month_days(first).last().unwrap() + Duration::days(1);
+        let query = TaskQuery::new()
+            .tag(self.tag_filter.clone())
+            .done(if self.show_done { None } else { Some(false) })
+            .due_after(Some(self.day_start(first)))
+            .due_before(Some(self.day_start(next_month) - Duration::seconds(1)));
+
+        self.calendar_counts.clear();
+        for task in db::query_tasks(self.conn, &query)? {
+            if let Some(limit) = task.limit {
+                *self.calendar_counts.entry(limit.date(self.zone)).or_default() += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Shows the calendar pane and moves the keyboard focus there.
+    pub fn open_calendar(&mut self) -> Result<()> {
+        if !self.show_calendar {
+            self.show_calendar = true;
+            self.calendar_day = self.day_filter.unwrap_or_else(|| self.zone.today());
+            self.refresh_calendar()?;
+        }
+        self.input_mode = InputMode::Calendar;
+        Ok(())
+    }
+
+    /// Hides the calendar pane and drops its day filter.
+    pub fn close_calendar(&mut self) -> Result<()> {
+        self.show_calendar = false;
+        self.input_mode = InputMode::Normal;
+        if self.day_filter.take().is_some() {
+            self.refresh_tasks()?;
+        }
+        Ok(())
+    }
+
+    /// Moves the calendar cursor by `days`, recounting when the month changes.
+    pub fn move_calendar(&mut self, days: i64) -> Result<()> {
+        let month = self.calendar_day.month();
+        self.calendar_day += Duration::days(days);
+        if self.calendar_day.month() != month {
+            self.refresh_calendar()?;
+        }
+        Ok(())
+    }
+
+    pub fn move_calendar_month(&mut self, forward: bool) -> Result<()> {
+        self.calendar_day = if forward {
+            self.calendar_day.checked_add_months(Months::new(1))
+        } else {
+            self.calendar_day.checked_sub_months(Months::new(1))
+        }
+        .unwrap_or(self.calendar_day);
+        self.refresh_calendar()
+    }
+
+    /// Lists only the tasks due on the day under the cursor, or all of them
+    /// again when that day is already selected.
+    pub fn toggle_day_filter(&mut self) -> Result<()> {
+        self.day_filter = if self.day_filter == Some(self.calendar_day) { None } else { Some(self.calendar_day) };
+        self.refresh_tasks()
+    }
+
+    /// Moves the selected task's deadline to the day under the calendar cursor,
+    /// keeping its time. Tasks without one get an all-day deadline.
+    pub fn reschedule_selected(&mut self) -> Result<()> {
+        if let Some(task) = self.state.selected().and_then(|i| self.filtered_tasks.get(i)) {
+            let mut task = task.clone();
+            task.limit = Some(match task.limit {
+                Some(limit) => limit.moved_to(self.calendar_day, self.zone),
+                None => db::Deadline::Day(self.calendar_day),
+            });
+            db::update_task(self.conn, &task)?;
+            self.refresh_tasks()?;
+        }
+        Ok(())
+    }
+
+    /// Selects the task `sakd next` would pick under the tag filter and
+    /// explains why.
+    pub fn suggest_next(&mut self) -> Result<()> {
+        let query = TaskQuery::new()
+            .tag(self.tag_filter.clone())
+            .done(Some(false))
+            .actionable_on(Some(self.zone.today()));
+        let tasks = db::query_tasks(self.conn, &query)?;
+        let ctx = score::Context {
+            now: Utc::now(),
+            zone: self.zone,
+            open_dependencies: db::open_dependencies(self.conn)?,
+        };
+        if let Some(best) = score::rank(&tasks, &ctx).into_iter().next() {
+            self.select_task(best.task_id)?;
+            self.input_mode = InputMode::Suggesting(best);
+        }
+        Ok(())
+    }
+
+    /// Selects task `id`, loading more pages until it's listed and dropping
+    /// the day filter if that hides it.
+    fn select_task(&mut self, id: i64) -> Result<()> {
+        loop {
+            if let Some(i) = self.filtered_tasks.iter().position(|t| t.id == id) {
+                self.state.select(Some(i));
+                return Ok(());
+            }
+            if self.has_more {
+                self.load_more()?;
+            } else if self.day_filter.is_some() {
+                self.day_filter = None;
+                self.refresh_tasks()?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Opens the statistics screen over all tasks.
+    pub fn open_stats(&mut self) -> Result<()> {
+        let tasks = db::get_tasks(self.conn)?;
+        self.input_mode = InputMode::Stats(stats::build(&tasks, STATS_DAYS, self.zone, Utc::now()));
+        Ok(())
+    }
+
+    /// Opens the burndown chart of the tag being filtered on (of all tasks
+    /// without a filter), from the first task's creation to today, over at
+    /// most `stats::BURNDOWN_DAYS` days.
+    pub fn open_burndown(&mut self) -> Result<()> {
+        let tasks = db::query_tasks(self.conn, &TaskQuery::new().tag(self.tag_filter.clone()))?;
+        let today = self.zone.naive(Utc::now()).date();
+        let from = stats::burndown_start(&tasks, self.zone, today);
+        self.input_mode = InputMode::Burndown(stats::burndown(&tasks, (from, today), self.zone));
+        Ok(())
+    }
+
+    /// Appends the next page of tasks, if any.
+    pub fn load_more(&mut self) -> Result<()> {
+        if !self.has_more {
+            return Ok(());
+        }
+        let page = db::query_tasks(self.conn, &self.query().limit(Some(PAGE_SIZE)).offset(self.tasks.len()))?;
+        self.has_more = page.len() == PAGE_SIZE;
+        self.tasks.extend(page);
         self.update_filtered_tasks();
+        Ok(())
+    }
+
+    /// Reloads the tasks if another session (e.g. the CLI) committed changes
+    /// since the last check. The selection is kept on the same task.
+    pub fn sync_external_changes(&mut self) -> Result<()> {
+        let version = db::data_version(self.conn)?;
+        if version != self.data_version {
+            self.data_version = version;
+            self.refresh_tasks()?;
+        }
+        Ok(())
+    }
+
+    pub fn toggle_done_visibility(&mut self) -> Result<()> {
+        self.show_done = !self.show_done;
+        self.refresh_tasks()
+    }
+
+    /// Fetches the next page when the selection reaches the last loaded task.
+    pub fn load_more_at_end(&mut self) -> Result<()> {
+        if self.state.selected().is_some_and(|i| i + 1 >= self.filtered_tasks.len()) {
+            self.load_more()?;
+        }
+        Ok(())
     }
 
     pub fn next(&mut self) {
@@ -165,28 +637,70 @@ impl<'a> App<'a> {
         self.state.select(Some(i));
     }
 
-    pub fn toggle_status(&mut self) -> Result<()> {
-        if let Some(i) = self.state.selected() {
-            let task_id = self.filtered_tasks[i].id;
-            if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
-                task.is_done = !task.is_done;
-                db::update_task(self.conn, task)?;
-            }
-            self.update_filtered_tasks();
-        }
+    /// Opens the tag browser with the current filter preselected. The first
+    /// row stands for "no filter".
+    pub fn open_tag_browser(&mut self) -> Result<()> {
+        self.tag_list = utils::build_tag_tree(&db::get_tag_assignments(self.conn)?);
+        let current = self.tag_filter.as_ref()
+            .and_then(|f| self.tag_list.iter().position(|n| n.path == *f))
+            .map_or(0, |i| i + 1);
+        self.tag_state.select(Some(current));
+        self.input_mode = InputMode::BrowsingTags;
         Ok(())
     }
 
-    pub fn start_add_popup(&mut self) {
-        self.popup_data = PopupData {
-            title: String::new(),
-            tags: String::new(),
-            date: String::new(),
-            time: String::new(),
-            description: String::new(),
+    pub fn move_tag_selection(&mut self, forward: bool) {
+        let len = self.tag_list.len() + 1;
+        let i = self.tag_state.selected().unwrap_or(0);
+        let i = if forward { (i + 1) % len } else { (i + len - 1) % len };
+        self.tag_state.select(Some(i));
+    }
+
+    pub fn apply_selected_tag(&mut self) -> Result<()> {
+        self.tag_filter = match self.tag_state.selected() {
+            Some(i) if i > 0 => self.tag_list.get(i - 1).map(|n| n.path.clone()),
+            _ => None,
         };
+        self.input_mode = InputMode::Normal;
+        self.refresh_tasks()
+    }
+
+    pub fn toggle_status(&mut self) -> Result<()> {
+        let Some(task) = self.state.selected().and_then(|i| self.filtered_tasks.get(i)) else {
+            return Ok(());
+        };
+        // Only the completion is written, so edits from other sessions survive
+        db::set_done(self.conn, task.id, !task.is_done)?;
+        self.refresh_tasks()
+    }
+
+    pub fn start_add_popup(&mut self) {
         self.input_buffer.clear();
-        self.input_mode = InputMode::Adding(PopupStep::Title);
+        self.input_mode = InputMode::QuickAdding;
+    }
+
+    /// Adds the task typed in the quick add line and selects it.
+    /// Does nothing while the line has no title.
+    pub fn save_quick_add(&mut self) -> Result<()> {
+        let parsed = utils::parse_quick_add(&self.input_buffer, self.zone);
+        if parsed.title.is_empty() {
+            return Ok(());
+        }
+
+        let mut task = Task::new(&parsed.title);
+        task.limit = parsed.limit;
+        task.priority = parsed.priority;
+        task.tags = parsed.tags;
+        task.estimate = parsed.estimate;
+        let id = db::add_task(self.conn, &task)?;
+
+        self.input_mode = InputMode::Normal;
+        self.input_buffer.clear();
+        self.refresh_tasks()?;
+        if let Some(i) = self.filtered_tasks.iter().position(|t| t.id == id) {
+            self.state.select(Some(i));
+        }
+        Ok(())
     }
 
     pub fn start_edit_popup(&mut self) {
@@ -194,23 +708,37 @@ impl<'a> App<'a> {
             let task = &self.filtered_tasks[i];
             self.popup_data = PopupData {
                 title: task.title.clone(),
+                priority: match task.priority {
+                    Priority::None => String::new(),
+                    p => format!("{:?}", p).to_lowercase(),
+                },
                 tags: task.tags.join(", "),
-                date: task.limit.map(|l| l.with_timezone(&chrono::Local).format("%Y/%m/%d").to_string())
+                date: task.limit.map(|l| l.own_local(self.zone).format("%Y/%m/%d").to_string())
                     .unwrap_or_default(),
-                time: task.limit.map(|l| l.with_timezone(&chrono::Local).format("%H:%M").to_string())
+                time: task.limit.filter(|l| !matches!(l, db::Deadline::Day(_)))
+                    .map(|l| l.own_local(self.zone).format("%H:%M").to_string())
                     .unwrap_or_default(),
                 description: task.description.clone().unwrap_or_default(),
             };
             self.input_buffer = self.popup_data.title.clone();
             self.input_mode = InputMode::Editing(task.id, PopupStep::Title);
+            self.edit_base = Some(task.clone());
         }
     }
 
     pub fn next_popup_step(&mut self) -> Result<()> {
         let (next_step, is_done) = match &self.input_mode {
-            InputMode::Adding(step) | InputMode::Editing(_, step) => match step {
+            InputMode::Editing(_, step) => match step {
                 PopupStep::Title => {
                     self.popup_data.title = self.input_buffer.clone();
+                    (PopupStep::Priority, false)
+                }
+                PopupStep::Priority => {
+                    // Stay on the step until the priority is one we know
+                    if utils::parse_priority(&self.input_buffer).is_none() {
+                        return Ok(());
+                    }
+                    self.popup_data.priority = self.input_buffer.clone();
                     (PopupStep::Tags, false)
                 }
                 PopupStep::Tags => {
@@ -235,17 +763,18 @@ impl<'a> App<'a> {
 
         if is_done {
             self.save_popup()?;
-            self.input_mode = InputMode::Normal;
+            if !matches!(self.input_mode, InputMode::Conflict(_)) {
+                self.input_mode = InputMode::Normal;
+            }
             self.input_buffer.clear();
         } else {
-            match &mut self.input_mode {
-                InputMode::Adding(s) => *s = next_step,
-                InputMode::Editing(_, s) => *s = next_step,
-                _ => {}
+            if let InputMode::Editing(_, s) = &mut self.input_mode {
+                *s = next_step;
             }
 
             self.input_buffer = match next_step {
                 PopupStep::Title => self.popup_data.title.clone(),
+                PopupStep::Priority => self.popup_data.priority.clone(),
                 PopupStep::Tags => self.popup_data.tags.clone(),
                 PopupStep::Date => self.popup_data.date.clone(),
                 PopupStep::Time => self.popup_data.time.clone(),
@@ -255,16 +784,26 @@ impl<'a> App<'a> {
         Ok(())
     }
 
+    /// Resolves an edit conflict, either writing the pending edit over the
+    /// other session's changes or dropping it.
+    pub fn resolve_conflict(&mut self, overwrite: bool) -> Result<()> {
+        if let Some(task) = self.pending_edit.take().filter(|_| overwrite) {
+            if db::get_task(self.conn, task.id)?.is_some() {
+                db::update_task(self.conn, &task)?;
+            } else {
+                db::add_task(self.conn, &task)?;
+            }
+        }
+        self.input_mode = InputMode::Normal;
+        self.refresh_tasks()
+    }
+
     pub fn save_popup(&mut self) -> Result<()> {
-        let date_str = self.popup_data.date.trim();
-        let time_str = self.popup_data.time.trim();
-        
-        let limit = if date_str.is_empty() {
-            None
-        } else {
-            let time = if time_str.is_empty() { "23:59" } else { time_str };
-            utils::parse_full_date_time(date_str, time)
-        };
+        // Keep the zone of the deadline being edited
+        let zone = self.edit_base.as_ref()
+            .and_then(|t| t.limit)
+            .map_or(DeadlineZone::Local, |l| l.zone());
+        let limit = utils::parse_deadline(&self.popup_data.date, &self.popup_data.time, zone, self.zone);
 
         let description = if self.popup_data.description.is_empty() {
             None
@@ -273,21 +812,27 @@ impl<'a> App<'a> {
         };
 
         let tags = utils::parse_tags(&self.popup_data.tags);
+        let priority = utils::parse_priority(&self.popup_data.priority).unwrap_or(Priority::None);
 
-        match self.input_mode {
-            InputMode::Adding(_) => {
-                db::add_task(self.conn, &self.popup_data.title, limit, description, tags)?;
-            }
-            InputMode::Editing(id, _) => {
-                if let Some(mut task) = db::get_task(self.conn, id)? {
-                    task.title = self.popup_data.title.clone();
-                    task.limit = limit;
-                    task.description = description;
-                    task.tags = tags;
+        if let InputMode::Editing(id, _) = self.input_mode {
+            let current = db::get_task(self.conn, id)?;
+            let base = self.edit_base.take();
+            if let Some(mut task) = base.clone().or_else(|| current.clone()) {
+                task.title = self.popup_data.title.clone();
+                task.priority = priority;
+                task.limit = limit;
+                task.description = description;
+                task.tags = tags;
+                // Being reordered elsewhere doesn't conflict with an edit
+                let content = |t: &Option<Task>| t.clone().map(|t| Task { position: 0, ..t });
+                if current.is_some() && (base.is_none() || content(&current) == content(&base)) {
                     db::update_task(self.conn, &task)?;
+                } else {
+                    // Modified or removed elsewhere since the popup opened
+                    self.pending_edit = Some(task);
+                    self.input_mode = InputMode::Conflict(id);
                 }
             }
-            _ => {}
         }
         self.refresh_tasks()?;
         Ok(())
@@ -322,106 +867,227 @@ pub fn run_tui(conn: &Connection) -> Result<TuiEvent> {
 
 fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> Result<TuiEvent> {
     loop {
+        app.sync_external_changes()?;
+        app.tick_pomodoro()?;
         terminal.draw(|f| ui(f, &mut app))?;
 
-        if event::poll(std::time::Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind != KeyEventKind::Press {
-                    continue;
-                }
-                match app.input_mode {
-                    InputMode::Normal => {
-                        match key.code {
-                            KeyCode::Char('q') | KeyCode::Esc => return Ok(TuiEvent::Quit),
-                            KeyCode::Char('j') | KeyCode::Down => app.next(),
-                            KeyCode::Char('k') | KeyCode::Up => app.previous(),
-                            KeyCode::Char(' ') | KeyCode::Enter => {
-                                app.toggle_status()?;
-                            }
-                            KeyCode::Char('r') => {
-                                if let Some(i) = app.state.selected() {
-                                    let task_id = app.filtered_tasks[i].id;
-                                    app.input_mode = InputMode::Deleting(task_id);
-                                }
-                            }
-                            KeyCode::Char('h') => {
-                                app.toggle_done_visibility();
-                            }
-                            KeyCode::Char('a') => {
-                                app.start_add_popup();
+        if event::poll(std::time::Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+        {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match app.input_mode {
+                InputMode::Normal => {
+                    match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => return Ok(TuiEvent::Quit),
+                        KeyCode::Char('j') | KeyCode::Down => {
+                            app.load_more_at_end()?;
+                            app.next();
+                        }
+                        KeyCode::Char('k') | KeyCode::Up => app.previous(),
+                        KeyCode::Char(' ') | KeyCode::Enter => {
+                            app.toggle_status()?;
+                        }
+                        KeyCode::Char('r') => {
+                            if let Some(i) = app.state.selected() {
+                                let task_id = app.filtered_tasks[i].id;
+                                app.input_mode = InputMode::Deleting(task_id);
                             }
-                             KeyCode::Char('e') => {
-                                app.start_edit_popup();
-                             }
-                            KeyCode::Char('f') => {
-                                app.input_mode = InputMode::FilteringTag;
-                                app.input_buffer = app.tag_filter.clone().unwrap_or_default();
-                             }
-                             KeyCode::Char('?') => {
-                                app.input_mode = InputMode::Helping;
-                             }
-                             _ => {}
+                        }
+                        KeyCode::Char('h') => {
+                            app.toggle_done_visibility()?;
+                        }
+                        KeyCode::Char('a') => {
+                            app.start_add_popup();
+                        }
+                         KeyCode::Char('e') => {
+                            app.start_edit_popup();
                          }
+                        KeyCode::Char('f') => {
+                            app.input_mode = InputMode::FilteringTag;
+                            app.input_buffer = app.tag_filter.clone().unwrap_or_default();
+                         }
+                        KeyCode::Char('t') => {
+                            app.open_tag_browser()?;
+                        }
+                        KeyCode::Char('c') => {
+                            app.open_calendar()?;
+                        }
+                        KeyCode::Char('b') => {
+                            app.open_board()?;
+                        }
+                        KeyCode::Char('s') => {
+                            app.suggest_next()?;
+                        }
+                        KeyCode::Char('o') => {
+                            app.cycle_sort()?;
+                        }
+                        KeyCode::Char('T') => {
+                            app.toggle_timer()?;
+                        }
+                        KeyCode::Char('p') => {
+                            app.toggle_pomodoro()?;
+                        }
+                        KeyCode::Char('S') => {
+                            app.open_stats()?;
+                        }
+                        KeyCode::Char('B') => {
+                            app.open_burndown()?;
+                        }
+                        KeyCode::Char('J') => {
+                            app.move_selected(true)?;
+                        }
+                        KeyCode::Char('K') => {
+                            app.move_selected(false)?;
+                        }
+                         KeyCode::Char('?') => {
+                            app.input_mode = InputMode::Helping;
+                         }
+                         _ => {}
+                     }
+                }
+                InputMode::Helping => {
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q') | KeyCode::Enter => {
+                            app.input_mode = InputMode::Normal;
+                        }
+                        _ => {}
                     }
-                    InputMode::Helping => {
-                        match key.code {
-                            KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q') | KeyCode::Enter => {
-                                app.input_mode = InputMode::Normal;
-                            }
-                            _ => {}
+                }
+                InputMode::QuickAdding => {
+                    match key.code {
+                        KeyCode::Enter => app.save_quick_add()?,
+                        KeyCode::Esc => {
+                            app.input_mode = InputMode::Normal;
+                            app.input_buffer.clear();
+                        }
+                        KeyCode::Char(c) => {
+                            app.input_buffer.push(c);
+                        }
+                        KeyCode::Backspace => {
+                            app.input_buffer.pop();
                         }
+                        _ => {}
                     }
-                    InputMode::Adding(_) | InputMode::Editing(_, _) => {
-                        match key.code {
-                            KeyCode::Enter => {
-                                app.next_popup_step()?;
-                            }
-                            KeyCode::Esc => {
-                                app.input_mode = InputMode::Normal;
-                                app.input_buffer.clear();
-                            }
-                            KeyCode::Char(c) => {
-                                app.input_buffer.push(c);
-                            }
-                            KeyCode::Backspace => {
-                                app.input_buffer.pop();
-                            }
-                            _ => {}
+                }
+                InputMode::Editing(_, _) => {
+                    match key.code {
+                        KeyCode::Enter => {
+                            app.next_popup_step()?;
                         }
+                        KeyCode::Esc => {
+                            app.input_mode = InputMode::Normal;
+                            app.input_buffer.clear();
+                        }
+                        KeyCode::Char(c) => {
+                            app.input_buffer.push(c);
+                        }
+                        KeyCode::Backspace => {
+                            app.input_buffer.pop();
+                        }
+                        _ => {}
                     }
-                    InputMode::Deleting(id) => {
-                        match key.code {
-                            KeyCode::Enter => {
-                                db::delete_task(app.conn, id)?;
-                                app.refresh_tasks()?;
-                                app.input_mode = InputMode::Normal;
-                            }
-                            KeyCode::Esc | KeyCode::Char('n') => {
-                                app.input_mode = InputMode::Normal;
-                            }
-                            _ => {}
+                }
+                InputMode::Deleting(id) => {
+                    match key.code {
+                        KeyCode::Enter => {
+                            db::delete_task(app.conn, id)?;
+                            app.refresh_tasks()?;
+                            app.input_mode = InputMode::Normal;
+                        }
+                        KeyCode::Esc | KeyCode::Char('n') => {
+                            app.input_mode = InputMode::Normal;
                         }
+                        _ => {}
                     }
-                    InputMode::FilteringTag => {
-                        match key.code {
-                            KeyCode::Enter => {
-                                app.tag_filter = if app.input_buffer.is_empty() { None } else { Some(app.input_buffer.clone()) };
-                                app.update_filtered_tasks();
-                                app.input_mode = InputMode::Normal;
-                                app.input_buffer.clear();
-                            }
-                            KeyCode::Esc => {
-                                app.input_mode = InputMode::Normal;
-                                app.input_buffer.clear();
-                            }
-                            KeyCode::Backspace => {
-                                app.input_buffer.pop();
-                            }
-                            KeyCode::Char(c) => {
-                                app.input_buffer.push(c);
-                            }
-                            _ => {}
+                }
+                InputMode::Conflict(_) => {
+                    match key.code {
+                        KeyCode::Enter => app.resolve_conflict(true)?,
+                        KeyCode::Esc | KeyCode::Char('n') => app.resolve_conflict(false)?,
+                        _ => {}
+                    }
+                }
+                InputMode::BrowsingTags => {
+                    match key.code {
+                        KeyCode::Char('j') | KeyCode::Down => app.move_tag_selection(true),
+                        KeyCode::Char('k') | KeyCode::Up => app.move_tag_selection(false),
+                        KeyCode::Enter => app.apply_selected_tag()?,
+                        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('t') => {
+                            app.input_mode = InputMode::Normal;
+                        }
+                        _ => {}
+                    }
+                }
+                InputMode::Calendar => {
+                    match key.code {
+                        KeyCode::Char('h') | KeyCode::Left => app.move_calendar(-1)?,
+                        KeyCode::Char('l') | KeyCode::Right => app.move_calendar(1)?,
+                        KeyCode::Char('k') | KeyCode::Up => app.move_calendar(-7)?,
+                        KeyCode::Char('j') | KeyCode::Down => app.move_calendar(7)?,
+                        KeyCode::Char('<') | KeyCode::Char('H') => app.move_calendar_month(false)?,
+                        KeyCode::Char('>') | KeyCode::Char('L') => app.move_calendar_month(true)?,
+                        KeyCode::Char('t') => {
+                            let today = app.zone.today();
+                            app.move_calendar((today - app.calendar_day).num_days())?;
+                        }
+                        KeyCode::Enter => app.toggle_day_filter()?,
+                        KeyCode::Char('m') => app.reschedule_selected()?,
+                        KeyCode::Tab => app.input_mode = InputMode::Normal,
+                        KeyCode::Esc | KeyCode::Char('c') | KeyCode::Char('q') => app.close_calendar()?,
+                        _ => {}
+                    }
+                }
+                InputMode::Stats(_) => {
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Char('S') | KeyCode::Char('q') => app.input_mode = InputMode::Normal,
+                        KeyCode::Char('r') => app.open_stats()?,
+                        _ => {}
+                    }
+                }
+                InputMode::Burndown(_) => {
+                    if let KeyCode::Esc | KeyCode::Char('B') | KeyCode::Char('q') = key.code {
+                        app.input_mode = InputMode::Normal;
+                    }
+                }
+                InputMode::Suggesting(_) => {
+                    app.input_mode = InputMode::Normal;
+                }
+                InputMode::Board => {
+                    match key.code {
+                        KeyCode::Left | KeyCode::BackTab => app.focus_board_column(-1),
+                        KeyCode::Right | KeyCode::Tab => app.focus_board_column(1),
+                        KeyCode::Char('j') | KeyCode::Down => app.select_next_card(true),
+                        KeyCode::Char('k') | KeyCode::Up => app.select_next_card(false),
+                        KeyCode::Char('h') | KeyCode::Char('H') => app.move_card(-1)?,
+                        KeyCode::Char('l') | KeyCode::Char('L') => app.move_card(1)?,
+                        KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('q') => {
+                            app.input_mode = InputMode::Normal;
+                            app.refresh_tasks()?;
+                        }
+                        _ => {}
+                    }
+                }
+                InputMode::FilteringTag => {
+                    match key.code {
+                        KeyCode::Enter => {
+                            app.tag_filter = if app.input_buffer.is_empty() { None } else { Some(app.input_buffer.clone()) };
+                            app.refresh_tasks()?;
+                            app.input_mode = InputMode::Normal;
+                            app.input_buffer.clear();
+                        }
+                        KeyCode::Esc => {
+                            app.input_mode = InputMode::Normal;
+                            app.input_buffer.clear();
                         }
+                        KeyCode::Backspace => {
+                            app.input_buffer.pop();
+                        }
+                        KeyCode::Char(c) => {
+                            app.input_buffer.push(c);
+                        }
+                        _ => {}
                     }
                 }
             }
@@ -455,7 +1121,255 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: ratatui::layout::Rect) -> ra
         .split(popup_layout[1])[1]
 }
 
+/// A tag rendered with its configured icon and color (inherited from the
+/// nearest ancestor tag). Completed tasks keep the dimmed list style.
+fn tag_span<'a>(metas: &HashMap<String, TagMeta>, tag: &str, dimmed: bool) -> Span<'a> {
+    let meta = db::find_tag_meta(metas, tag);
+    let icon = meta.and_then(|m| m.icon.as_deref()).map(|i| format!("{} ", i)).unwrap_or_default();
+    let text = format!("{}#{}", icon, tag);
+
+    let color = if dimmed {
+        Color::DarkGray
+    } else {
+        match utils::tag_color(meta.and_then(|m| m.color.as_deref())) {
+            utils::TagColor::Rgb(r, g, b) => Color::Rgb(r, g, b),
+            utils::TagColor::Ansi(8) => Color::DarkGray,
+            utils::TagColor::Ansi(i) => Color::Indexed(i),
+        }
+    };
+    Span::styled(text, Style::default().fg(color))
+}
+
+/// A list row: `text` followed by the task's tags, dimmed once it is done.
+fn task_item<'a>(app: &App, task: &Task, text: String) -> ListItem<'a> {
+    let mut spans = vec![Span::raw(text)];
+    for tag in &task.tags {
+        spans.push(Span::raw(" "));
+        spans.push(tag_span(&app.tag_metas, tag, task.is_done));
+    }
+    let style = if task.is_done {
+        Style::default().fg(Color::DarkGray)
+    } else {
+        Style::default()
+    };
+    ListItem::new(Line::from(spans)).style(style)
+}
+
+/// One column per workflow state, the focused one outlined in green.
+fn render_board(f: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
+    let area = Layout::default().margin(1).constraints([Constraint::Min(0)].as_ref()).split(area)[0];
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Ratio(1, app.board.len() as u32); app.board.len()])
+        .split(area);
+
+    for (n, tasks) in app.board.iter().enumerate() {
+        let items: Vec<ListItem> = tasks
+            .iter()
+            .map(|t| task_item(app, t, format!("{:>2}: {} {}", t.id, t.priority.to_symbol(), t.title)))
+            .collect();
+        let focused = n == app.board_column;
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" {} ({}) ", app.workflow.states[n], tasks.len()))
+                    .border_style(if focused { Style::default().fg(Color::Green) } else { Style::default() }),
+            )
+            .highlight_style(if focused {
+                Style::default().bg(Color::Blue).fg(Color::White).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().add_modifier(Modifier::BOLD)
+            });
+        f.render_stateful_widget(list, columns[n], &mut app.board_states[n]);
+    }
+}
+
+/// Month grid around the calendar cursor. Days with tasks due are bold and
+/// colored by how many there are.
+fn render_calendar(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let focused = matches!(app.input_mode, InputMode::Calendar);
+    let first = app.calendar_day.with_day(1).unwrap();
+    let today = app.zone.today();
+
+    let mut lines = vec![Line::from(Span::styled(" Mo Tu We Th Fr Sa Su", Style::default().fg(Color::DarkGray)))];
+    let mut week = vec![Span::raw("   ".repeat(first.weekday().num_days_from_monday() as usize))];
+    for day in utils::month_days(first) {
+        let mut style = match app.calendar_counts.get(&day) {
+            Some(1) => Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+            Some(2) => Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            Some(_) => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            None => Style::default(),
+        };
+        if day == today {
+            style = style.add_modifier(Modifier::UNDERLINED);
+        }
+        if app.day_filter == Some(day) {
+            style = style.bg(Color::DarkGray);
+        }
+        if day == app.calendar_day {
+            style = if focused { style.bg(Color::Blue).fg(Color::White) } else { style.add_modifier(Modifier::REVERSED) };
+        }
+        week.push(Span::raw(" "));
+        week.push(Span::styled(format!("{:>2}", day.day()), style));
+        if day.weekday() == chrono::Weekday::Sun {
+            lines.push(Line::from(std::mem::take(&mut week)));
+        }
+    }
+    if !week.is_empty() {
+        lines.push(Line::from(week));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(first.format(" %Y-%m ").to_string())
+        .border_style(if focused { Style::default().fg(Color::Green) } else { Style::default() });
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn render_pomodoro(f: &mut Frame, app: &App, pomodoro: &Pomodoro, area: ratatui::layout::Rect) {
+    let now = Utc::now();
+    let (phase, color) = match pomodoro.phase {
+        Phase::Work => ("Work", Color::Red),
+        Phase::Break => ("Break", Color::Green),
+    };
+    let count = app.pomodoros.get(&pomodoro.task_id).copied().unwrap_or(0);
+    let seconds = pomodoro.remaining(now).num_seconds();
+    let gauge = Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            " \u{1F345} {}: {}  ({} done) [p: Stop] ",
+            pomodoro.task_id, app.pomodoro_title, count
+        )))
+        .gauge_style(Style::default().fg(color))
+        .ratio(pomodoro.progress(now))
+        .label(format!("{} {:02}:{:02}", phase, seconds / 60, seconds % 60));
+    f.render_widget(gauge, area);
+}
+
+fn render_stats(f: &mut Frame, stats: &Stats, area: ratatui::layout::Rect) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(7), Constraint::Length(10), Constraint::Min(0)].as_ref())
+        .split(area);
+
+    let daily: Vec<u64> = stats.daily.iter().map(|(_, n)| *n as u64).collect();
+    let today = stats.daily.last().map_or(0, |(_, n)| *n);
+    let sparkline = Sparkline::default()
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            " Completed per Day (last {} days, today {}) [r: Refresh, S/Esc: Close] ",
+            daily.len(), today
+        )))
+        .style(Style::default().fg(Color::Green))
+        .data(&daily);
+    f.render_widget(sparkline, rows[0]);
+
+    let labels: Vec<String> = stats.weekly.iter().map(|(week, _)| week.format("%m/%d").to_string()).collect();
+    let weekly: Vec<(&str, u64)> = labels.iter().zip(&stats.weekly).map(|(l, (_, n))| (l.as_str(), *n as u64)).collect();
+    let chart = BarChart::default()
+        .block(Block::default().borders(Borders::ALL).title(" Completed per Week "))
+        .data(&weekly)
+        .bar_width(5)
+        .bar_gap(2)
+        .bar_style(Style::default().fg(Color::Cyan))
+        .value_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    f.render_widget(chart, rows[1]);
+
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+        .split(rows[2]);
+
+    let overdue_style = if stats.overdue > 0 { Style::default().fg(Color::Red) } else { Style::default() };
+    let overview = vec![
+        Line::from(format!("Open: {}", stats.open)),
+        Line::from(Span::styled(format!("Overdue: {}", stats.overdue), overdue_style)),
+        Line::from(format!("Done: {}", stats.done)),
+        Line::from(format!("Average lead time: {}", stats.lead_time.map_or("-".to_string(), stats::format_lead_time))),
+        Line::from(format!("Streak: {} day(s) (best {})", stats.streak, stats.best_streak)),
+    ];
+    f.render_widget(Paragraph::new(overview).block(Block::default().borders(Borders::ALL).title(" Overview ")), bottom[0]);
+
+    let bars: Vec<Bar> = stats.tags.iter().map(|rate| {
+        Bar::default()
+            .label(Line::from(format!("#{}", rate.tag)))
+            .value(rate.percent())
+            .text_value(format!("{}% ({}/{})", rate.percent(), rate.done, rate.total))
+    }).collect();
+    let tags = BarChart::default()
+        .block(Block::default().borders(Borders::ALL).title(" Completion by Tag "))
+        .direction(Direction::Horizontal)
+        .data(BarGroup::default().bars(&bars))
+        .max(100)
+        .bar_width(1)
+        .bar_gap(0)
+        .bar_style(Style::default().fg(Color::Green))
+        .value_style(Style::default().fg(Color::White));
+    f.render_widget(tags, bottom[1]);
+}
+
+fn render_burndown(f: &mut Frame, tag: Option<&str>, points: &[BurndownPoint], area: ratatui::layout::Rect) {
+    let series = |value: fn(&BurndownPoint) -> usize| -> Vec<(f64, f64)> {
+        points.iter().enumerate().map(|(i, p)| (i as f64, value(p) as f64)).collect()
+    };
+    let open = series(|p| p.open);
+    let completed = series(|p| p.completed);
+    let ideal: Vec<(f64, f64)> = stats::ideal(points).into_iter().enumerate().map(|(i, v)| (i as f64, v)).collect();
+    let datasets = vec![
+        Dataset::default().name("Ideal").marker(symbols::Marker::Braille).graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::DarkGray)).data(&ideal),
+        Dataset::default().name("Open").marker(symbols::Marker::Braille).graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Yellow)).data(&open),
+        Dataset::default().name("Completed").marker(symbols::Marker::Braille).graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Green)).data(&completed),
+    ];
+
+    let max = points.iter().map(|p| p.open.max(p.completed)).max().unwrap_or(0).max(1);
+    let day = |i: usize| points.get(i).map_or(String::new(), |p| p.day.format("%m/%d").to_string());
+    let last = points.len().saturating_sub(1);
+    let title = format!(
+        " Burndown {} ({} - {}) [B/Esc: Close] ",
+        tag.map_or("(all tasks)".to_string(), |t| format!("#{}", t)),
+        day(0),
+        day(last)
+    );
+    let chart = Chart::new(datasets)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)))
+        .x_axis(Axis::default()
+            .bounds([0.0, last.max(1) as f64])
+            .labels(vec![Span::raw(day(0)), Span::raw(day(last / 2)), Span::raw(day(last))]))
+        .y_axis(Axis::default()
+            .bounds([0.0, max as f64])
+            .labels(vec![Span::raw("0"), Span::raw((max / 2).to_string()), Span::raw(max.to_string())]));
+    let area = Layout::default().margin(1).constraints([Constraint::Min(0)].as_ref()).split(area)[0];
+    f.render_widget(chart, area);
+}
+
 fn ui(f: &mut Frame, app: &mut App) {
+    let mut area = f.size();
+    if let Some(timer) = &app.timer {
+        let header = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+            .split(area);
+        let seconds = timer.duration(Utc::now()).num_seconds().max(0);
+        let text = format!(
+            " \u{23F1} {:02}:{:02}:{:02}  {}: {}  [T: Stop]",
+            seconds / 3600, seconds / 60 % 60, seconds % 60, timer.task_id, app.timer_title
+        );
+        f.render_widget(Paragraph::new(text).style(Style::default().fg(Color::Black).bg(Color::Yellow)), header[0]);
+        area = header[1];
+    }
+    if let Some(pomodoro) = &app.pomodoro {
+        let footer = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
+            .split(area);
+        render_pomodoro(f, app, pomodoro, footer[1]);
+        area = footer[0];
+    }
+
     let main_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .margin(1)
@@ -466,30 +1380,37 @@ fn ui(f: &mut Frame, app: &mut App) {
             ]
             .as_ref(),
         )
-        .split(f.size());
+        .split(area);
+
+    if let InputMode::Board = app.input_mode {
+        render_board(f, app, area);
+        return;
+    }
+    if let InputMode::Stats(stats) = &app.input_mode {
+        render_stats(f, stats, area);
+        return;
+    }
+    if let InputMode::Burndown(points) = &app.input_mode {
+        render_burndown(f, app.tag_filter.as_deref(), points, area);
+        return;
+    }
 
     let tasks: Vec<ListItem> = app
         .filtered_tasks
         .iter()
         .map(|i| {
             let status = if i.is_done { "[v]" } else { "[ ]" };
-            
-            let content = format!("{:>2}: {} {}", i.id, status, i.title);
-            let style = if i.is_done {
-                Style::default().fg(Color::DarkGray)
-            } else {
-                Style::default()
-            };
-            
-            ListItem::new(content).style(style)
+            task_item(app, i, format!("{:>2}: {} {} {}", i.id, status, i.priority.to_symbol(), i.title))
         })
         .collect();
 
     let tasks_list = List::new(tasks)
         .block(Block::default().borders(Borders::ALL).title(format!(
-            " Tasks ({}) [Filter: Tag:{}] (? for help) ", 
+            " Tasks ({}) [Sort: {}] [Filter: Tag:{}]{} (? for help) ", 
             if app.show_done { "All" } else { "Active" },
+            SORT_ORDERS[app.sort],
             app.tag_filter.as_ref().unwrap_or(&"None".to_string()),
+            app.day_filter.map(|d| d.format(" [Day: %m/%d]").to_string()).unwrap_or_default(),
         )))
         .highlight_style(
             Style::default()
@@ -501,6 +1422,17 @@ fn ui(f: &mut Frame, app: &mut App) {
 
     f.render_stateful_widget(tasks_list, main_chunks[0], &mut app.state);
 
+    let detail_area = if app.show_calendar {
+        let right_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(10), Constraint::Min(0)].as_ref())
+            .split(main_chunks[1]);
+        render_calendar(f, app, right_chunks[0]);
+        right_chunks[1]
+    } else {
+        main_chunks[1]
+    };
+
     // Detail Panel
     let selected_index = app.state.selected();
     let detail_block = Block::default().borders(Borders::ALL).title(" Details ");
@@ -508,45 +1440,117 @@ fn ui(f: &mut Frame, app: &mut App) {
     if let Some(i) = selected_index {
         if let Some(task) = app.filtered_tasks.get(i) {
             let mut details = Vec::new();
-            details.push(format!("Title: {}", task.title));
-            details.push(format!("Tags:  {}", task.tags.join(", ")));
+            details.push(Line::from(format!("Title: {}", task.title)));
+            details.push(Line::from(format!("Priority: {:?}", task.priority)));
+            details.push(Line::from(format!("State: {}", app.workflow.state_name(task))));
+
+            let mut tag_line = vec![Span::raw("Tags:  ")];
+            for (n, tag) in task.tags.iter().enumerate() {
+                if n > 0 {
+                    tag_line.push(Span::raw(", "));
+                }
+                tag_line.push(tag_span(&app.tag_metas, tag, false));
+            }
+            details.push(Line::from(tag_line));
             
             if let Some(limit) = task.limit {
-                details.push(format!("Limit: {}", limit.with_timezone(&chrono::Local).format("%Y/%m/%d %H:%M")));
+                let zone = limit.zone_label().map(|z| format!(" ({})", z)).unwrap_or_default();
+                details.push(Line::from(format!("Limit: {}{}", limit.format(app.zone, "%Y/%m/%d"), zone)));
             } else {
-                details.push("Limit: None".to_string());
+                details.push(Line::from("Limit: None"));
+            }
+            let day = |d: Option<chrono::NaiveDate>| d.map_or("None".to_string(), |d| d.format("%Y/%m/%d").to_string());
+            details.push(Line::from(format!("Scheduled: {}", day(task.scheduled))));
+            if task.wait_until.is_some() {
+                details.push(Line::from(format!("Wait until: {}", day(task.wait_until))));
+            }
+            if !task.dependencies.is_empty() {
+                let deps: Vec<String> = task.dependencies.iter().map(|id| id.to_string()).collect();
+                details.push(Line::from(format!("Depends on: {}", deps.join(", "))));
+            }
+            let tracked = app.tracked_time(task.id);
+            match (task.estimate, tracked > Duration::zero()) {
+                (Some(estimate), true) => details.push(Line::from(format!(
+                    "Tracked: {} of {} estimated",
+                    utils::format_duration(tracked),
+                    utils::format_duration(estimate)
+                ))),
+                (Some(estimate), false) => details.push(Line::from(format!("Estimate: {}", utils::format_duration(estimate)))),
+                (None, true) => details.push(Line::from(format!("Tracked: {}", utils::format_duration(tracked)))),
+                (None, false) => {}
+            }
+            if let Some(count) = app.pomodoros.get(&task.id) {
+                details.push(Line::from(format!("Pomodoros: {}", count)));
             }
 
             if let Some(desc) = &task.description {
-                details.push("".to_string());
-                details.push("Description:".to_string());
-                details.push(desc.clone());
+                details.push(Line::from(""));
+                details.push(Line::from("Description:"));
+                details.push(Line::from(desc.clone()));
             }
 
-            let detail_text = Paragraph::new(details.join("\n")).block(detail_block);
-            f.render_widget(detail_text, main_chunks[1]);
+            let detail_text = Paragraph::new(details).block(detail_block);
+            f.render_widget(detail_text, detail_area);
         } else {
-            f.render_widget(Paragraph::new("No task selected").block(detail_block), main_chunks[1]);
+            f.render_widget(Paragraph::new("No task selected").block(detail_block), detail_area);
         }
     } else {
-        f.render_widget(Paragraph::new("No task selected").block(detail_block), main_chunks[1]);
+        f.render_widget(Paragraph::new("No task selected").block(detail_block), detail_area);
     }
 
     // Popup for Add/Edit
     match &app.input_mode {
-        InputMode::Adding(step) | InputMode::Editing(_, step) => {
+        InputMode::QuickAdding => {
+            let area = centered_rect(60, 30, f.size());
+            let parsed = utils::parse_quick_add(&app.input_buffer, app.zone);
+
+            let popup_block = Block::default()
+                .title(" Adding Task... ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan));
+
+            let label = |s: &'static str| Span::styled(format!("  {:<9} ", s), Style::default().fg(Color::DarkGray));
+            let mut tag_spans = vec![label("Tags:")];
+            for tag in &parsed.tags {
+                tag_spans.push(tag_span(&app.tag_metas, tag, false));
+                tag_spans.push(Span::raw(" "));
+            }
+            let limit = parsed.limit
+                .map(|l| l.format(app.zone, "%Y/%m/%d"))
+                .unwrap_or_else(|| "None".to_string());
+
+            let lines = vec![
+                Line::from(vec![
+                    Span::styled("> ", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(app.input_buffer.clone()),
+                ]),
+                Line::from(""),
+                Line::from(vec![label("Title:"), Span::raw(parsed.title.clone())]),
+                Line::from(vec![label("Limit:"), Span::raw(limit)]),
+                Line::from(tag_spans),
+                Line::from(vec![label("Priority:"), Span::raw(format!("{:?}", parsed.priority))]),
+                Line::from(vec![label("Estimate:"), Span::raw(parsed.estimate.map_or("None".to_string(), utils::format_duration))]),
+                Line::from(""),
+                Line::from(Span::styled(
+                    "  e.g. Send report tomorrow 15:00 #work !high   [Enter] Add [Esc] Cancel",
+                    Style::default().fg(Color::DarkGray),
+                )),
+            ];
+
+            let popup_text = Paragraph::new(lines).block(popup_block);
+            f.render_widget(ratatui::widgets::Clear, area);
+            f.render_widget(popup_text, area);
+        }
+        InputMode::Editing(_, step) => {
             let area = centered_rect(60, 20, f.size());
-            let title = match &app.input_mode {
-                InputMode::Adding(_) => " Adding Task... ",
-                InputMode::Editing(_, _) => " Editing Task... ",
-                _ => "",
-            };
+            let title = " Editing Task... ";
             
             let (prompt, help) = match step {
                 PopupStep::Title => ("Title:", "(Required)"),
+                PopupStep::Priority => ("Priority:", "h (high), m (medium), l (low) or empty"),
                 PopupStep::Tags => ("Tags:", "Comma separated"),
-                PopupStep::Date => ("Date:", "YYYY/MM/DD or MM/DD ( shortcuts: t, tm, 2d, 1w... )"),
-                PopupStep::Time => ("Time:", "HH:MM ( shortcuts: last, noon, 1h... )"),
+                PopupStep::Date => ("Date:", "YYYY/MM/DD, YYYY-MM-DD or MM/DD ( shortcuts: t, tm, 2d, next mon, eom, 明日, 来週月曜... )"),
+                PopupStep::Time => ("Time:", "HH:MM, empty for all day ( shortcuts: last, noon, 1h, 午後3時, 夕方... )"),
                 PopupStep::Description => ("Desc:", "(Optional)"),
             };
 
@@ -583,6 +1587,23 @@ fn ui(f: &mut Frame, app: &mut App) {
             f.render_widget(ratatui::widgets::Clear, area);
             f.render_widget(popup_text, area);
         }
+        InputMode::Conflict(id) => {
+            let area = centered_rect(60, 20, f.size());
+            let popup_block = Block::default()
+                .title(" Edit Conflict ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow));
+
+            let popup_text = Paragraph::new(format!(
+                "\n  Task {} was changed in another session while you were editing.\n\n  [Enter] Save my changes  [Esc/n] Discard my changes",
+                id
+            ))
+                .block(popup_block)
+                .alignment(ratatui::layout::Alignment::Center);
+
+            f.render_widget(ratatui::widgets::Clear, area);
+            f.render_widget(popup_text, area);
+        }
         InputMode::FilteringTag => {
             let area = centered_rect(60, 20, f.size());
             let block = Block::default().title(" Filter by Tag ").borders(Borders::ALL);
@@ -592,6 +1613,49 @@ fn ui(f: &mut Frame, app: &mut App) {
             f.render_widget(ratatui::widgets::Clear, area);
             f.render_widget(text, area);
         }
+        InputMode::BrowsingTags => {
+            let area = centered_rect(40, 60, f.size());
+            let mut items = vec![ListItem::new("(all tasks)").style(Style::default().fg(Color::DarkGray))];
+            items.extend(app.tag_list.iter().map(|node| {
+                let branch = if node.depth == 0 { String::new() } else { format!("{}└ ", "  ".repeat(node.depth - 1)) };
+                ListItem::new(format!("{}{} ({})", branch, node.name, node.count))
+            }));
+
+            let list = List::new(items)
+                .block(
+                    Block::default()
+                        .title(" Tags [Enter] Filter [Esc] Close ")
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::Cyan)),
+                )
+                .highlight_style(Style::default().bg(Color::Blue).fg(Color::White).add_modifier(Modifier::BOLD))
+                .highlight_symbol(">> ");
+
+            f.render_widget(ratatui::widgets::Clear, area);
+            f.render_stateful_widget(list, area, &mut app.tag_state);
+        }
+        InputMode::Suggesting(score) => {
+            let area = centered_rect(50, 30, f.size());
+            let title = app.filtered_tasks.iter().find(|t| t.id == score.task_id).map_or("", |t| t.title.as_str());
+            let mut lines = vec![
+                Line::from(vec![Span::styled(format!("{}: {}", score.task_id, title), Style::default().add_modifier(Modifier::BOLD))]),
+                Line::from(format!("Score: {}", score.total)),
+                Line::from(""),
+            ];
+            for factor in &score.factors {
+                let color = if factor.points < 0 { Color::Red } else { Color::Green };
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{:+4} ", factor.points), Style::default().fg(color)),
+                    Span::raw(format!("{:<12} {}", factor.name, factor.reason)),
+                ]));
+            }
+            let block = Block::default()
+                .title(" Do This Next [any key: Close] ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow));
+            f.render_widget(ratatui::widgets::Clear, area);
+            f.render_widget(Paragraph::new(lines).block(block), area);
+        }
         InputMode::Helping => {
             let area = centered_rect(80, 80, f.size());
             let block = Block::default().title(" Detailed Help ").borders(Borders::ALL).border_style(Style::default().fg(Color::Green));
@@ -602,16 +1666,40 @@ fn ui(f: &mut Frame, app: &mut App) {
                 "a: Add Task",
                 "e: Edit Task",
                 "r: Remove Task",
-                "h: Toggle Completed Visibility",
+                "h: Toggle Completed & Waiting Visibility",
                 "f: Filter by Tag",
+                "t: Browse Tags",
+                "s: Jump to the Task to Do Now (with the reasons)",
+                "o: Cycle Sort (limit, priority, newest, title, ID, manual)",
+                "J/K: Move Task Down/Up in the Manual Order",
+                "T: Start/Stop Tracking Time on the Task",
+                "p: Start/Stop a Pomodoro on the Task (bell on each phase change)",
+                "S: Statistics (r: Refresh, S/Esc: Close)",
+                "B: Burndown of the Filtered Tag (B/Esc: Close)",
+                "c: Calendar",
+                "b: Board (h/l: Move Task to Previous/Next State, \u{2190}/\u{2192}: Column, b/Esc: Back)",
                 "q/Esc: Quit",
                 "",
+                "=== Calendar (c) ===",
+                "h/l/j/k or arrows: Move Day/Week, H/L or </>: Month, t: Today",
+                "Enter: Show Tasks Due That Day, m: Move Selected Task There",
+                "Tab: Back to List, c/Esc: Close",
+                "",
+                "=== Quick Add (a) ===",
+                "Send report tomorrow 15:00 #work !high",
+                "      #tag: tag, !high/!!!, !med/!!, !low/!: priority, ~2h: estimate",
+                "      Date at the end, or @fri / due:fri anywhere; \"...\" is always title",
+                "",
                 "=== Input Format (Add/Edit) ===",
-                "Date: YYYY/MM/DD, MM/DD",
-                "      Shortcuts: t (today), tm (tomorrow), 2d, 1w...",
-                "      Day: mon, tue, wed, thu, fri, sat, sun",
-                "Time: HH:MM",
+                "Date: YYYY/MM/DD, YYYY-MM-DD, MM/DD, 2026-W10 (ISO week)",
+                "      Shortcuts: t (today), tm (tomorrow), yd (yesterday)",
+                "      Offsets: 2d, 1w, 3m, 1y, +2w, -1d, in 3 days",
+                "      Day: mon..sun, this fri, next mon",
+                "      End of: eow, eom, eoy / next week, next month",
+                "      Japanese: 今日, 明日, 明後日, 来週月曜, 3日後, 月末, 3月1日",
+                "Time: HH:MM (empty for an all-day limit)",
                 "      Shortcuts: last (23:59), noon (12:00), 1h...",
+                "      Japanese: 午後3時, 15時30分, 朝, 昼, 夕方, 夜",
                 "",
                 "[Esc/?: Close]"
             ];
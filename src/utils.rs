@@ -1,104 +1,927 @@
-use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Datelike};
+use chrono::{DateTime, Duration, Local, LocalResult, Months, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc, Datelike, Weekday};
+use chrono_tz::Tz;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::db::{Deadline, Priority, SortField, SortKey, Task};
 
 pub fn parse_shortcut_date(s: &str) -> Option<NaiveDate> {
-    let s = s.to_lowercase();
-    let now = Local::now().date_naive();
-    
+    parse_shortcut_date_on(s, Local::now().date_naive())
+}
+
+/// Relative date expressions, resolved against `today`:
+///
+/// - `t`/`today`, `tm`/`tomorrow`, `yd`/`yesterday`
+/// - `Nd`, `Nw`, `Nm` (months), `Ny`, also signed (`+2w`, `-1d`) or spelled out (`in 3 days`)
+/// - `mon`..`sun` (next such day), `this fri` (in the current week), `next mon` (in the following week)
+/// - `next week`/`next month`/`next year` (their first day)
+/// - `eow`, `eom`, `eoy` (last day of the current week, month, year)
+///
+/// Japanese expressions are understood as well, see `parse_japanese_date`.
+pub fn parse_shortcut_date_on(s: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let s = normalize_width(s).trim().to_lowercase();
+    if let Some(date) = parse_japanese_date(&s, today) {
+        return Some(date);
+    }
+    let words: Vec<&str> = s.split_whitespace().collect();
+
+    match words.as_slice() {
+        ["today" | "t"] => Some(today),
+        ["tomorrow" | "tm"] => Some(today + Duration::days(1)),
+        ["yesterday" | "yd"] => Some(today - Duration::days(1)),
+        ["eow"] => Some(week_start(today) + Duration::days(6)),
+        ["eom"] => month_start(today).checked_add_months(Months::new(1)).map(|d| d - Duration::days(1)),
+        ["eoy"] => NaiveDate::from_ymd_opt(today.year(), 12, 31),
+        ["next", "week"] => Some(week_start(today) + Duration::days(7)),
+        ["next", "month"] => month_start(today).checked_add_months(Months::new(1)),
+        ["next", "year"] => NaiveDate::from_ymd_opt(today.year() + 1, 1, 1),
+        ["this", day] => Some(week_start(today) + Duration::days(parse_weekday(day)?.num_days_from_monday() as i64)),
+        ["next", day] => Some(week_start(today) + Duration::days(7 + parse_weekday(day)?.num_days_from_monday() as i64)),
+        ["in", n, unit] => add_offset(today, n.parse().ok()?, unit),
+        [word] => {
+            if let Some(target) = parse_weekday(word) {
+                let mut date = today + Duration::days(1);
+                while date.weekday() != target {
+                    date += Duration::days(1);
+                }
+                return Some(date);
+            }
+
+            // [+-][N](d|w|m|y)
+            let split = word.find(|c: char| c.is_ascii_alphabetic())?;
+            let (n, unit) = word.split_at(split);
+            let n: i64 = n.strip_prefix('+').unwrap_or(n).parse().ok()?;
+            if unit.len() != 1 {
+                return None;
+            }
+            add_offset(today, n, unit)
+        }
+        _ => None,
+    }
+}
+
+/// Converts full-width digits, letters and symbols (as typed with a Japanese
+/// IME) to ASCII, so `１５：００` reads as `15:00`.
+pub fn normalize_width(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            '\u{3000}' => ' ',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Japanese date expressions: `今日`, `明日`, `明後日`, `昨日`, `月曜`,
+/// `来週月曜` (also `来週の月曜日`, `今週金曜`, `再来週水曜`), `3日後`, `2週間後`,
+/// `1ヶ月後`, `1年後`, `2日前`, `週末`, `月末`, `年末`, `来週`, `来月`, `来年`,
+/// `2026年3月1日` and `3月1日`.
+fn parse_japanese_date(s: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let s: String = s.chars().filter(|c| !c.is_whitespace() && *c != 'の').collect();
     match s.as_str() {
-        "today" | "t" => return Some(now),
-        "tomorrow" | "tm" => return Some(now + Duration::days(1)),
+        "今日" | "本日" => return Some(today),
+        "明日" => return Some(today + Duration::days(1)),
+        "明後日" => return Some(today + Duration::days(2)),
+        "昨日" => return Some(today - Duration::days(1)),
+        "週末" | "今週末" => return parse_shortcut_date_on("eow", today),
+        "月末" => return parse_shortcut_date_on("eom", today),
+        "年末" => return parse_shortcut_date_on("eoy", today),
+        "来週" => return parse_shortcut_date_on("next week", today),
+        "来月" => return parse_shortcut_date_on("next month", today),
+        "来年" => return parse_shortcut_date_on("next year", today),
         _ => {}
     }
 
-    // [N]d, [N]w
-    if s.ends_with('d') {
-        if let Ok(n) = s[..s.len()-1].parse::<i64>() {
-            return Some(now + Duration::days(n));
+    for (prefix, weeks) in [("再来週", 2), ("来週", 1), ("今週", 0)] {
+        if let Some(rest) = s.strip_prefix(prefix) {
+            let day = parse_japanese_weekday(rest, false)?;
+            return Some(week_start(today) + Duration::days(weeks * 7 + day.num_days_from_monday() as i64));
         }
     }
-    if s.ends_with('w') {
-        if let Ok(n) = s[..s.len()-1].parse::<i64>() {
-            return Some(now + Duration::days(n * 7));
-        }
+    if let Some(target) = parse_japanese_weekday(&s, true) {
+        return parse_shortcut_date_on(&format!("{:?}", target), today);
     }
 
-    // mon, tue, wed, thu, fri, sat, sun
-    let target_weekday = match s.as_str() {
-        "mon" => Some(chrono::Weekday::Mon),
-        "tue" => Some(chrono::Weekday::Tue),
-        "wed" => Some(chrono::Weekday::Wed),
-        "thu" => Some(chrono::Weekday::Thu),
-        "fri" => Some(chrono::Weekday::Fri),
-        "sat" => Some(chrono::Weekday::Sat),
-        "sun" => Some(chrono::Weekday::Sun),
-        _ => None,
+    // N日後, N週間後, Nヶ月後, N年後 (and 前 for the past)
+    let (rest, sign) = match (s.strip_suffix('後'), s.strip_suffix('前')) {
+        (Some(rest), _) => (Some(rest), 1),
+        (_, Some(rest)) => (Some(rest), -1),
+        _ => (None, 1),
+    };
+    if let Some(rest) = rest {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let (n, unit) = rest.split_at(digits);
+        let unit = match unit {
+            "日" => "d",
+            "週" | "週間" => "w",
+            "ヶ月" | "か月" | "カ月" | "ヵ月" | "箇月" => "m",
+            "年" => "y",
+            _ => return None,
+        };
+        return add_offset(today, sign * n.parse::<i64>().ok()?, unit);
+    }
+
+    // [YYYY年]M月D日
+    let (year, rest) = match s.split_once('年') {
+        Some((y, rest)) => (y.parse().ok()?, rest),
+        None => (today.year(), s.as_str()),
     };
+    let (month, day) = rest.split_once('月')?;
+    NaiveDate::from_ymd_opt(year, month.parse().ok()?, day.strip_suffix('日')?.parse().ok()?)
+}
 
-    if let Some(target) = target_weekday {
-        let mut date = now + Duration::days(1);
-        while date.weekday() != target {
-            date += Duration::days(1);
-        }
-        return Some(date);
+/// `月曜`/`月曜日` (or just `月` when `require_suffix` is false) through `日曜`.
+fn parse_japanese_weekday(s: &str, require_suffix: bool) -> Option<Weekday> {
+    let mut chars = s.chars();
+    let day = match chars.next()? {
+        '月' => Weekday::Mon,
+        '火' => Weekday::Tue,
+        '水' => Weekday::Wed,
+        '木' => Weekday::Thu,
+        '金' => Weekday::Fri,
+        '土' => Weekday::Sat,
+        '日' => Weekday::Sun,
+        _ => return None,
+    };
+    match chars.as_str() {
+        "曜" | "曜日" => Some(day),
+        "" if !require_suffix => Some(day),
+        _ => None,
+    }
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    match s {
+        "mon" | "monday" => Some(Weekday::Mon),
+        "tue" | "tuesday" => Some(Weekday::Tue),
+        "wed" | "wednesday" => Some(Weekday::Wed),
+        "thu" | "thursday" => Some(Weekday::Thu),
+        "fri" | "friday" => Some(Weekday::Fri),
+        "sat" | "saturday" => Some(Weekday::Sat),
+        "sun" | "sunday" => Some(Weekday::Sun),
+        _ => None,
     }
+}
 
-    None
+/// `date` moved by `n` units, or `None` when that leaves the calendar.
+fn add_offset(date: NaiveDate, n: i64, unit: &str) -> Option<NaiveDate> {
+    let days = |d: i64| date.checked_add_signed(Duration::try_days(d)?);
+    let months = |m: i64| {
+        let count = Months::new(u32::try_from(m.unsigned_abs()).ok()?);
+        if m >= 0 { date.checked_add_months(count) } else { date.checked_sub_months(count) }
+    };
+    match unit {
+        "d" | "day" | "days" => days(n),
+        "w" | "week" | "weeks" => days(n.checked_mul(7)?),
+        "m" | "month" | "months" => months(n),
+        "y" | "year" | "years" => months(n.checked_mul(12)?),
+        _ => None,
+    }
+}
+
+/// Monday of the (ISO) week containing `date`.
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+/// The first day of a month written as `2026-03`, `2026/3`, `3`, `mar`, `march`,
+/// `3月` or `2026年3月`. The year defaults to that of `today`.
+pub fn parse_month(s: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let s = normalize_width(s).trim().to_lowercase();
+    let s = s.strip_suffix('月').unwrap_or(&s);
+    let (year, month) = match s.split_once(['-', '/', '年']) {
+        Some((year, month)) => (year.parse().ok()?, month),
+        None => (today.year(), s),
+    };
+    let month = match month.parse::<u32>() {
+        Ok(m) => m,
+        Err(_) => month.parse::<chrono::Month>().ok()?.number_from_month(),
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+}
+
+/// Every day of the month starting at `first`.
+pub fn month_days(first: NaiveDate) -> impl Iterator<Item = NaiveDate> {
+    first.iter_days().take_while(move |d| d.month() == first.month())
 }
 
 pub fn parse_shortcut_time(s: &str) -> Option<NaiveTime> {
-    let s = s.to_lowercase();
+    let s = normalize_width(s).trim().to_lowercase();
     match s.as_str() {
         "last" => return Some(NaiveTime::from_hms_opt(23, 59, 0).unwrap()),
-        "morning" => return Some(NaiveTime::from_hms_opt(9, 0, 0).unwrap()),
-        "noon" => return Some(NaiveTime::from_hms_opt(12, 0, 0).unwrap()),
-        "evening" => return Some(NaiveTime::from_hms_opt(18, 0, 0).unwrap()),
-        "night" => return Some(NaiveTime::from_hms_opt(21, 0, 0).unwrap()),
+        "morning" | "朝" => return Some(NaiveTime::from_hms_opt(9, 0, 0).unwrap()),
+        "noon" | "昼" | "正午" => return Some(NaiveTime::from_hms_opt(12, 0, 0).unwrap()),
+        "evening" | "夕方" => return Some(NaiveTime::from_hms_opt(18, 0, 0).unwrap()),
+        "night" | "夜" => return Some(NaiveTime::from_hms_opt(21, 0, 0).unwrap()),
         _ => {}
     }
 
-    // [N]h
-    if s.ends_with('h') {
-        if let Ok(n) = s[..s.len()-1].parse::<i64>() {
-            let now = Local::now();
-            let target = now + Duration::hours(n);
-            return Some(target.time());
-        }
+    // [N]h, N時間後
+    if let Some(n) = s.strip_suffix('h').or_else(|| s.strip_suffix("時間後")).and_then(|n| n.parse::<i64>().ok()) {
+        let target = Local::now().checked_add_signed(Duration::try_hours(n)?)?;
+        return Some(target.time());
+    }
+
+    parse_japanese_clock(&s)
+}
+
+/// `15時`, `15時30分`, `午後3時`, `午前9時半`.
+fn parse_japanese_clock(s: &str) -> Option<NaiveTime> {
+    let (pm, rest) = match (s.strip_prefix("午後"), s.strip_prefix("午前")) {
+        (Some(rest), _) => (Some(true), rest),
+        (_, Some(rest)) => (Some(false), rest),
+        _ => (None, s),
+    };
+    let (hour, minute) = rest.split_once('時')?;
+    let mut hour: u32 = hour.parse().ok()?;
+    let minute: u32 = match minute {
+        "" => 0,
+        "半" => 30,
+        m => m.strip_suffix('分')?.parse().ok()?,
+    };
+    match pm {
+        Some(true) if hour < 12 => hour += 12,
+        Some(false) if hour == 12 => hour = 0,
+        _ => {}
     }
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/// A clock time: `HH:MM` or a Japanese clock time such as `午後3時`.
+fn parse_clock_time(s: &str) -> Option<NaiveTime> {
+    let s = normalize_width(s);
+    NaiveTime::parse_from_str(s.trim(), "%H:%M").ok().or_else(|| parse_japanese_clock(s.trim()))
+}
 
-    None
+/// A time of day as written in prompts: `HH:MM` or a shortcut (`noon`, `2h`, `午後3時`...).
+pub fn parse_time(s: &str) -> Option<NaiveTime> {
+    parse_shortcut_time(s).or_else(|| parse_clock_time(s))
 }
 
-pub fn parse_full_date_time(date_str: &str, time_str: &str) -> Option<DateTime<Utc>> {
+/// The instant a date and time name in `zone`; the end of the day without a time.
+pub fn parse_full_date_time(date_str: &str, time_str: &str, zone: DisplayZone) -> Option<DateTime<Utc>> {
     if date_str.trim().is_empty() {
         return None;
     }
 
-    let date = parse_shortcut_date(date_str)
-        .or_else(|| NaiveDate::parse_from_str(date_str, "%Y/%m/%d").ok())
-        .or_else(|| {
-            let now = Local::now().date_naive();
-            NaiveDate::parse_from_str(&format!("{}/{}", now.year(), date_str), "%Y/%m/%d").ok()
-        })?;
+    let date = parse_date(date_str)?;
 
     let time = if time_str.trim().is_empty() {
-        NaiveTime::from_hms_opt(23, 59, 0).unwrap()
+        NaiveTime::from_hms_opt(23, 59, 59).unwrap()
     } else {
-        parse_shortcut_time(time_str)
-            .or_else(|| NaiveTime::parse_from_str(time_str, "%H:%M").ok())
-            .unwrap_or_else(|| NaiveTime::from_hms_opt(23, 59, 0).unwrap())
+        parse_time(time_str).unwrap_or_else(|| NaiveTime::from_hms_opt(23, 59, 0).unwrap())
     };
 
-    let dt = NaiveDateTime::new(date, time);
-    Local.from_local_datetime(&dt).single().map(|dt| dt.with_timezone(&Utc))
+    Some(zone.resolve(NaiveDateTime::new(date, time)))
+}
+
+/// Parses date and time input into a deadline. Without a time it's an all-day
+/// deadline; with one it's that wall time in `zone` (`DeadlineZone::Local` being
+/// the display zone).
+pub fn parse_deadline(date_str: &str, time_str: &str, zone: DeadlineZone, display: DisplayZone) -> Option<Deadline> {
+    if date_str.trim().is_empty() {
+        return None;
+    }
+
+    let date = parse_date(date_str)?;
+    match parse_time(time_str) {
+        Some(time) => Some(Deadline::Floating(NaiveDateTime::new(date, time)).rezone(zone, display)),
+        None => Some(Deadline::Day(date)),
+    }
+}
+
+/// The zone deadlines are shown and entered in: the `timezone` setting, or the
+/// system zone when unset.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum DisplayZone {
+    #[default]
+    Local,
+    Named(Tz),
+}
+
+impl DisplayZone {
+    /// `local` or an IANA zone name such as `Asia/Tokyo`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "" | "local" => Some(DisplayZone::Local),
+            name => name.parse().ok().map(DisplayZone::Named),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            DisplayZone::Local => "local".to_string(),
+            DisplayZone::Named(tz) => tz.name().to_string(),
+        }
+    }
+
+    /// The wall time of `at` in this zone.
+    pub fn naive(&self, at: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            DisplayZone::Local => at.with_timezone(&Local).naive_local(),
+            DisplayZone::Named(tz) => at.with_timezone(tz).naive_local(),
+        }
+    }
+
+    /// The current date in this zone.
+    pub fn today(&self) -> NaiveDate {
+        self.naive(Utc::now()).date()
+    }
+
+    /// See `resolve_local`.
+    pub fn resolve(&self, naive: NaiveDateTime) -> DateTime<Utc> {
+        match self {
+            DisplayZone::Local => resolve_local(&Local, naive),
+            DisplayZone::Named(tz) => resolve_local(tz, naive),
+        }
+    }
+
+    /// See `local_time_note`.
+    pub fn note(&self, naive: NaiveDateTime) -> Option<&'static str> {
+        match self {
+            DisplayZone::Local => local_time_note(&Local, naive),
+            DisplayZone::Named(tz) => local_time_note(tz, naive),
+        }
+    }
+}
+
+/// The zone a deadline's wall time is read in, as given with `--tz`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DeadlineZone {
+    /// The display zone at the time of entry; stored as a plain instant.
+    Local,
+    /// Follows the display zone, so the deadline doesn't shift when traveling.
+    Floating,
+    Named(Tz),
+}
+
+impl DeadlineZone {
+    /// `local`, `floating` or an IANA zone name.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "local" => Some(DeadlineZone::Local),
+            "floating" => Some(DeadlineZone::Floating),
+            name => name.parse().ok().map(DeadlineZone::Named),
+        }
+    }
+}
+
+/// Why a wall time entered for a deadline in `zone` had to be adjusted, if it
+/// did (see `resolve_local`). Floating deadlines are never adjusted.
+pub fn deadline_note(naive: NaiveDateTime, zone: DeadlineZone, display: DisplayZone) -> Option<&'static str> {
+    match zone {
+        DeadlineZone::Local => display.note(naive),
+        DeadlineZone::Floating => None,
+        DeadlineZone::Named(tz) => local_time_note(&tz, naive),
+    }
+}
+
+/// Workflow states in board order, set with `sakd config set workflow`. The
+/// first state is where new tasks start and the last one means done, so tasks
+/// only need to remember the states in between.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Workflow {
+    pub states: Vec<String>,
+}
+
+impl Default for Workflow {
+    fn default() -> Self {
+        Workflow { states: ["todo", "in-progress", "review", "done"].map(String::from).to_vec() }
+    }
+}
+
+impl Workflow {
+    /// A comma separated list of at least two distinct state names.
+    pub fn parse(s: &str) -> Option<Self> {
+        let states: Vec<String> = s.split(',').map(|st| st.trim().to_lowercase()).collect();
+        let distinct: HashSet<&String> = states.iter().collect();
+        if states.len() < 2 || distinct.len() != states.len() || states.iter().any(|st| st.is_empty()) {
+            return None;
+        }
+        Some(Workflow { states })
+    }
+
+    fn last(&self) -> usize {
+        self.states.len() - 1
+    }
+
+    /// Index of the task's state. Open tasks in a state that is no longer
+    /// configured are back in the first one.
+    pub fn column(&self, task: &Task) -> usize {
+        if task.is_done {
+            return self.last();
+        }
+        task.state.as_ref()
+            .and_then(|state| self.states[..self.last()].iter().position(|st| st == state))
+            .unwrap_or(0)
+    }
+
+    pub fn state_name(&self, task: &Task) -> &str {
+        &self.states[self.column(task)]
+    }
+
+    /// Puts the task in the state at `column`; the last state marks it done.
+    pub fn set_column(&self, task: &mut Task, column: usize) {
+        let column = column.min(self.last());
+        task.is_done = column == self.last();
+        task.state = if column == 0 || task.is_done { None } else { Some(self.states[column].clone()) };
+    }
+
+    /// Puts the task in the state named `name`, if there is one.
+    pub fn set_state(&self, task: &mut Task, name: &str) -> bool {
+        match self.states.iter().position(|st| *st == name.trim().to_lowercase()) {
+            Some(column) => {
+                self.set_column(task, column);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Settings understood by `sakd config`, with what they do.
+pub const SETTINGS: &[(&str, &str)] = &[
+    ("timezone", "zone deadlines are shown and entered in (IANA name or `local`)"),
+    ("workflow", "workflow states, comma separated; the first is where tasks start, the last means done"),
+    ("capacity", "hours of work `sakd plan` fits into a day (default 8h)"),
+    ("pomodoro-work", "length of a pomodoro in the TUI (default 25m)"),
+    ("pomodoro-break", "length of the break after each pomodoro (default 5m)"),
+];
+
+/// Checks `value` for the setting `key` and returns it normalized.
+pub fn validate_setting(key: &str, value: &str) -> Result<String, String> {
+    match key {
+        "timezone" => DisplayZone::parse(value)
+            .map(|z| z.name())
+            .ok_or_else(|| format!("Unknown time zone '{}'. Use an IANA name such as Asia/Tokyo, or local.", value)),
+        "workflow" => Workflow::parse(value)
+            .map(|w| w.states.join(","))
+            .ok_or_else(|| "Give at least two distinct states separated by commas, e.g. todo,doing,done.".to_string()),
+        "capacity" | "pomodoro-work" | "pomodoro-break" => parse_duration(value)
+            .filter(|d| *d > Duration::zero())
+            .map(format_duration)
+            .ok_or_else(|| format!("Invalid {} '{}'. Use a duration such as 25m or 7h30m.", key, value)),
+        _ => Err(format!("Unknown setting '{}'.", key)),
+    }
+}
+
+/// The instant a wall time names in `zone`. As in iCalendar (RFC 5545), a time
+/// that occurs twice when clocks go back means the first occurrence, and a time
+/// skipped when they go forward is read with the offset from before the jump,
+/// so 02:30 on the night of a one-hour gap becomes 03:30.
+pub fn resolve_local<Z: TimeZone>(zone: &Z, naive: NaiveDateTime) -> DateTime<Utc> {
+    match zone.from_local_datetime(&naive) {
+        LocalResult::Single(dt) | LocalResult::Ambiguous(dt, _) => dt.with_timezone(&Utc),
+        LocalResult::None => {
+            let before = zone.offset_from_utc_datetime(&(naive - Duration::days(1))).fix();
+            Utc.from_utc_datetime(&(naive - Duration::seconds(before.local_minus_utc() as i64)))
+        }
+    }
+}
+
+/// Explains how `resolve_local` reads a wall time that isn't unique in `zone`.
+pub fn local_time_note<Z: TimeZone>(zone: &Z, naive: NaiveDateTime) -> Option<&'static str> {
+    match zone.from_local_datetime(&naive) {
+        LocalResult::Single(_) => None,
+        LocalResult::Ambiguous(..) => Some("occurs twice as clocks go back; using the first occurrence"),
+        LocalResult::None => Some("is skipped as clocks go forward; moved past the jump"),
+    }
+}
+
+/// `h`/`high`, `m`/`medium`/`med`, `l`/`low`, and `none` or nothing for no
+/// priority. Anything else is rejected.
+pub fn parse_priority(s: &str) -> Option<Priority> {
+    match s.trim().to_lowercase().as_str() {
+        "h" | "high" => Some(Priority::High),
+        "m" | "med" | "medium" => Some(Priority::Medium),
+        "l" | "low" => Some(Priority::Low),
+        "" | "n" | "none" => Some(Priority::None),
+        _ => None,
+    }
+}
+
+/// Minutes in the working day `1d` stands for in estimates.
+pub const WORKDAY_MINUTES: i64 = 8 * 60;
+
+/// Longest duration `parse_duration` accepts, about a hundred years, so that
+/// adding or summing durations can't overflow.
+const MAX_DURATION_MINUTES: f64 = 100.0 * 365.0 * 24.0 * 60.0;
+
+/// An effort such as `30m`, `2h`, `1h30m`, `1.5h` or `1d` (a working day,
+/// see `WORKDAY_MINUTES`). Japanese units work too: `30分`, `2時間`, `1日`.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = normalize_width(s).trim().to_lowercase();
+    let mut minutes = 0.0;
+    let mut rest = s.as_str();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let number_len = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+        let value: f64 = rest[..number_len].parse().ok()?;
+        rest = rest[number_len..].trim_start();
+        let unit_len = rest.find(|c: char| c.is_ascii_digit() || c.is_whitespace()).unwrap_or(rest.len());
+        let unit = match &rest[..unit_len] {
+            "m" | "min" | "mins" | "minute" | "minutes" | "分" => 1.0,
+            "h" | "hr" | "hrs" | "hour" | "hours" | "時間" => 60.0,
+            "d" | "day" | "days" | "日" => WORKDAY_MINUTES as f64,
+            _ => return None,
+        };
+        minutes += value * unit;
+        rest = rest[unit_len..].trim_start();
+    }
+    if !minutes.is_finite() || minutes > MAX_DURATION_MINUTES {
+        return None;
+    }
+    Duration::try_minutes(minutes.round() as i64)
+}
+
+/// A tracked duration as "2h 05m", or "25m" under an hour.
+pub fn format_duration(d: Duration) -> String {
+    let minutes = d.num_minutes().max(0);
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+/// A comma separated sort specification such as `limit,-created,title`. A
+/// leading `-` reverses a field's natural order.
+pub fn parse_sort(s: &str) -> Result<Vec<SortKey>, String> {
+    s.split(',')
+        .map(|key| {
+            let key = key.trim().to_lowercase();
+            let (descending, name) = match key.strip_prefix('-') {
+                Some(name) => (true, name),
+                None => (false, key.strip_prefix('+').unwrap_or(&key)),
+            };
+            let field = match name {
+                "limit" | "due" | "deadline" => SortField::Limit,
+                "priority" | "pri" => SortField::Priority,
+                "created" => SortField::Created,
+                "scheduled" => SortField::Scheduled,
+                "title" => SortField::Title,
+                "id" => SortField::Id,
+                "manual" | "position" => SortField::Position,
+                _ => return Err(format!(
+                    "Unknown sort field '{}'. Use limit, priority, created, scheduled, title, id or manual.",
+                    name
+                )),
+            };
+            Ok(SortKey { field, descending })
+        })
+        .collect()
+}
+
+/// The specification `parse_sort` reads back as `keys`.
+pub fn format_sort(keys: &[SortKey]) -> String {
+    keys.iter()
+        .map(|k| format!("{}{}", if k.descending { "-" } else { "" }, k.field.name()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Colors accepted for tags, besides `#rrggbb`.
+pub const TAG_COLORS: [&str; 9] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white", "gray"];
+
+/// A tag color as both frontends draw it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TagColor {
+    /// Index into `TAG_COLORS`, which follows the ANSI order; `gray` (8) is
+    /// bright black.
+    Ansi(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// Maps a stored tag color to what the CLI and TUI show; tags without one
+/// (or with an unreadable one) are cyan.
+pub fn tag_color(name: Option<&str>) -> TagColor {
+    const CYAN: TagColor = TagColor::Ansi(6);
+    let Some(name) = name else {
+        return CYAN;
+    };
+    if let Some(hex) = name.strip_prefix('#') {
+        return match u32::from_str_radix(hex, 16) {
+            Ok(rgb) if hex.len() == 6 => TagColor::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8),
+            _ => CYAN,
+        };
+    }
+    TAG_COLORS.iter().position(|c| *c == name).map_or(CYAN, |i| TagColor::Ansi(i as u8))
+}
+
+/// Validates a tag color, returning its canonical spelling.
+pub fn normalize_color(s: &str) -> Option<String> {
+    let s = s.trim().to_lowercase();
+    let s = if s == "grey" { "gray".to_string() } else { s };
+    let is_hex = s.len() == 7 && s.starts_with('#') && s[1..].chars().all(|c| c.is_ascii_hexdigit());
+    (is_hex || TAG_COLORS.contains(&s.as_str())).then_some(s)
+}
+
+/// Result of parsing a one-line task such as
+/// `Send report tomorrow 15:00 #work #urgent !high`.
+#[derive(Debug, PartialEq)]
+pub struct QuickAdd {
+    pub title: String,
+    pub limit: Option<Deadline>,
+    pub tags: Vec<String>,
+    pub priority: Priority,
+    pub estimate: Option<Duration>,
+}
+
+/// Parts of a quick-add line that are given some other way, such as flags.
+/// `parse_quick_add_except` leaves their words in the title.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct QuickAddGiven {
+    pub limit: bool,
+    pub tags: bool,
+    pub priority: bool,
+    pub estimate: bool,
+}
+
+/// Longest run of words tried as a single date expression.
+const MAX_DATE_WORDS: usize = 3;
+
+/// A word of quick-add input; quoted words are always part of the title.
+struct Word<'a> {
+    text: std::borrow::Cow<'a, str>,
+    quoted: bool,
+}
+
+/// Splits on whitespace, keeping `"quoted text"` together and unquoted.
+fn quick_add_words(input: &str) -> Vec<Word<'_>> {
+    let mut words = Vec::new();
+    let mut rest = input.trim_start();
+    while !rest.is_empty() {
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            words.push(Word { text: quoted[..end].into(), quoted: true });
+            rest = quoted.get(end + 1..).unwrap_or("");
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            words.push(Word { text: rest[..end].into(), quoted: false });
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+    }
+    words
+}
+
+/// Splits a one-line task into its parts:
+///
+/// - `#tag` adds a tag (`#work/api` for nested tags)
+/// - `!high`, `!h`, `!!!` (high), `!!` (medium), `!` or `!low` (low) set the priority
+/// - `~2h`, `~30m` sets the estimate (see `parse_duration`)
+/// - a date (`tomorrow`, `fri`, `2d`, `2026/03/01`...) at the end of the title
+///   sets the limit, optionally followed by a time (`15:00`, `noon`...); a bare
+///   `HH:MM` there means today
+/// - `@date` or `due:date` sets the limit anywhere in the line
+///
+/// Everything else, in order, is the title. Words in double quotes are always
+/// title, so `Watch the "sun"` keeps its last word.
+///
+/// Times are read in the display zone `zone`; a date without a time gives an
+/// all-day deadline.
+pub fn parse_quick_add(input: &str, zone: DisplayZone) -> QuickAdd {
+    parse_quick_add_except(input, zone, QuickAddGiven::default())
+}
+
+/// `parse_quick_add`, reading only the parts not already `given`.
+pub fn parse_quick_add_except(input: &str, zone: DisplayZone, given: QuickAddGiven) -> QuickAdd {
+    let input = normalize_width(input);
+    let words = quick_add_words(&input);
+    let mut title: Vec<&Word> = Vec::new();
+    let mut tags = Vec::new();
+    let mut priority = Priority::None;
+    let mut estimate = None;
+    let mut date: Option<String> = None;
+    let mut time: Option<String> = None;
+
+    let mut i = 0;
+    while i < words.len() {
+        let word = &words[i];
+        i += 1;
+        if word.quoted {
+            title.push(word);
+            continue;
+        }
+        let text = word.text.as_ref();
+
+        if !given.tags
+            && let Some(tag) = text.strip_prefix('#').map(normalize_tag).filter(|t| !t.is_empty()) {
+            tags.push(tag);
+            continue;
+        }
+        if !given.priority
+            && let Some(p) = parse_priority_marker(text) {
+            priority = p;
+            continue;
+        }
+        if !given.estimate
+            && let Some(e) = text.strip_prefix('~').and_then(parse_duration) {
+            estimate = Some(e);
+            continue;
+        }
+        if !given.limit
+            && date.is_none()
+            && let Some(marked) = text.strip_prefix('@').or_else(|| strip_prefix_ignore_case(text, "due:")) {
+            // The date may start in the marker word (`@fri`) or after it (`due: fri`)
+            let mut candidates: Vec<&str> = words[i..].iter().take_while(|w| !w.quoted).map(|w| w.text.as_ref()).collect();
+            let skip = usize::from(!marked.is_empty());
+            if !marked.is_empty() {
+                candidates.insert(0, marked);
+            }
+            if let Some((n, d, t)) = date_at_start(&candidates) {
+                date = Some(d);
+                time = t;
+                i += n - skip;
+                continue;
+            }
+        }
+
+        title.push(word);
+    }
+
+    // Without a marker, only a date at the very end of the title counts
+    if !given.limit && date.is_none() {
+        let plain: Vec<&str> = title.iter().rev().take_while(|w| !w.quoted).map(|w| w.text.as_ref()).collect();
+        let plain: Vec<&str> = plain.into_iter().rev().collect();
+        // Keep at least one word of title
+        let keep = usize::from(plain.len() == title.len());
+        if let Some((n, d, t)) = date_at_end(&plain[keep..]) {
+            title.truncate(title.len() - n);
+            date = d;
+            time = t;
+        }
+    }
+
+    let limit = match (&date, &time) {
+        (Some(d), t) => parse_deadline(d, t.as_deref().unwrap_or(""), DeadlineZone::Local, zone),
+        (None, Some(t)) => parse_deadline("today", t, DeadlineZone::Local, zone),
+        (None, None) => None,
+    };
+
+    QuickAdd {
+        title: title.iter().map(|w| w.text.as_ref()).collect::<Vec<_>>().join(" "),
+        limit,
+        tags,
+        priority,
+        estimate,
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    s.get(..prefix.len()).filter(|p| p.eq_ignore_ascii_case(prefix)).map(|_| &s[prefix.len()..])
+}
+
+/// The longest date (and optional time) `words` start with: the number of
+/// words it takes, the date and the time.
+fn date_at_start(words: &[&str]) -> Option<(usize, String, Option<String>)> {
+    if let Some(n) = (1..=MAX_DATE_WORDS.min(words.len())).rev().find(|&n| parse_date(&words[..n].join(" ")).is_some()) {
+        let date = words[..n].join(" ");
+        return Some(match words.get(n).filter(|w| parse_time(w).is_some()) {
+            Some(t) => (n + 1, date, Some(t.to_string())),
+            None => (n, date, None),
+        });
+    }
+    // Date and time written together, as usual in Japanese (明日15時)
+    let (d, t) = split_date_time(words.first()?)?;
+    Some((1, d.to_string(), Some(t.to_string())))
+}
+
+/// A date, time or both that `words` end with: the number of words it takes,
+/// the date and the time.
+fn date_at_end(words: &[&str]) -> Option<(usize, Option<String>, Option<String>)> {
+    let last = *words.last()?;
+    let date_before = |end: usize| {
+        (1..=MAX_DATE_WORDS.min(end)).rev().find(|&n| parse_date(&words[end - n..end].join(" ")).is_some())
+    };
+    // Time words like "noon" only count after a date, so "Morning run" stays a title
+    if parse_time(last).is_some()
+        && let Some(n) = date_before(words.len() - 1) {
+        let date = words[words.len() - 1 - n..words.len() - 1].join(" ");
+        return Some((n + 1, Some(date), Some(last.to_string())));
+    }
+    if parse_clock_time(last).is_some() {
+        return Some((1, None, Some(last.to_string())));
+    }
+    if let Some(n) = date_before(words.len()) {
+        return Some((n, Some(words[words.len() - n..].join(" ")), None));
+    }
+    let (d, t) = split_date_time(last)?;
+    Some((1, Some(d.to_string()), Some(t.to_string())))
+}
+
+fn split_date_time(word: &str) -> Option<(&str, &str)> {
+    word.char_indices()
+        .skip(1)
+        .map(|(i, _)| word.split_at(i))
+        .find(|(d, t)| parse_date(d).is_some() && parse_time(t).is_some())
+}
+
+fn parse_priority_marker(word: &str) -> Option<Priority> {
+    let rest = word.strip_prefix('!')?;
+    match rest {
+        "!!" => Some(Priority::High),
+        "!" => Some(Priority::Medium),
+        "" => Some(Priority::Low),
+        _ => parse_priority(rest).filter(|p| *p != Priority::None),
+    }
+}
+
+/// A calendar date written as a shortcut (`tm`, `fri`...), `YYYY/MM/DD`,
+/// `YYYY-MM-DD`, an ISO week date (`2026-W10`, `2026-W10-5`) or `MM/DD`.
+pub fn parse_date(date_str: &str) -> Option<NaiveDate> {
+    parse_date_on(date_str, Local::now().date_naive())
+}
+
+pub fn parse_date_on(date_str: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let date_str = normalize_width(date_str);
+    let date_str = date_str.trim();
+    parse_shortcut_date_on(date_str, today)
+        .or_else(|| NaiveDate::parse_from_str(date_str, "%Y/%m/%d").ok())
+        .or_else(|| NaiveDate::parse_from_str(date_str, "%Y-%m-%d").ok())
+        .or_else(|| parse_iso_week_date(date_str))
+        .or_else(|| NaiveDate::parse_from_str(&format!("{}/{}", today.year(), date_str), "%Y/%m/%d").ok())
+}
+
+/// `YYYY-Www` (that week's Monday) or `YYYY-Www-D` with D from 1 (Monday) to 7.
+fn parse_iso_week_date(s: &str) -> Option<NaiveDate> {
+    let s = s.to_uppercase();
+    let (year, rest) = s.split_once("-W")?;
+    let (week, day) = match rest.split_once('-') {
+        Some((w, d)) => (w, d.parse::<u8>().ok()?),
+        None => (rest, 1),
+    };
+    let weekday = Weekday::try_from(day.checked_sub(1)?).ok()?;
+    NaiveDate::from_isoywd_opt(year.parse().ok()?, week.parse().ok()?, weekday)
 }
 
 pub fn parse_tags(s: &str) -> Vec<String> {
     s.split(',')
-        .map(|t| t.trim().to_string())
+        .map(normalize_tag)
         .filter(|t| !t.is_empty())
         .collect()
 }
 
+/// Tags are `/`-separated paths (e.g. `work/backend/api`). Trims every
+/// segment and drops empty ones, so `" work / api/ "` becomes `work/api`.
+pub fn normalize_tag(s: &str) -> String {
+    s.split('/')
+        .map(str::trim)
+        .filter(|seg| !seg.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Whether two tag names mean the same tag once normalized, ignoring case.
+pub fn same_tag(a: &str, b: &str) -> bool {
+    normalize_tag(a).eq_ignore_ascii_case(&normalize_tag(b))
+}
+
+/// Whether `tag` is `filter` itself or one of its descendants, ignoring ASCII
+/// case like SQLite's `lower()` in `TaskQuery`. `work` matches `work` and
+/// `work/api`, but not `homework` or `workshop`.
+pub fn tag_matches(tag: &str, filter: &str) -> bool {
+    let tag = tag.to_ascii_lowercase();
+    let filter = normalize_tag(filter).to_ascii_lowercase();
+    tag == filter || tag.strip_prefix(&filter).is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// One row of the tag hierarchy; `count` is the number of distinct tasks
+/// carrying this tag or any of its descendants.
+#[derive(Debug, PartialEq)]
+pub struct TagNode {
+    pub path: String,
+    pub name: String,
+    pub depth: usize,
+    pub count: usize,
+}
+
+/// Builds the tag hierarchy from `(task_id, tag)` pairs, in depth-first order.
+/// Intermediate levels that are never used as a tag on their own still get a row.
+pub fn build_tag_tree(assignments: &[(i64, String)]) -> Vec<TagNode> {
+    let mut nodes: BTreeMap<Vec<&str>, HashSet<i64>> = BTreeMap::new();
+    for (task_id, tag) in assignments {
+        let segments: Vec<&str> = tag.split('/').collect();
+        for depth in 1..=segments.len() {
+            nodes.entry(segments[..depth].to_vec()).or_default().insert(*task_id);
+        }
+    }
+
+    nodes.into_iter()
+        .map(|(segments, tasks)| TagNode {
+            path: segments.join("/"),
+            name: segments.last().unwrap_or(&"").to_string(),
+            depth: segments.len() - 1,
+            count: tasks.len(),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -109,19 +932,367 @@ mod tests {
         let now = Local::now().date_naive();
         
         // YYYY/MM/DD
-        let dt = parse_full_date_time("2026/02/10", "12:00").unwrap();
+        let dt = parse_full_date_time("2026/02/10", "12:00", DisplayZone::Local).unwrap();
         assert_eq!(dt.with_timezone(&Local).year(), 2026);
         assert_eq!(dt.with_timezone(&Local).month(), 2);
         assert_eq!(dt.with_timezone(&Local).day(), 10);
 
         // MM/DD (Current year)
-        let dt = parse_full_date_time("05/20", "10:30").unwrap();
+        let dt = parse_full_date_time("05/20", "10:30", DisplayZone::Local).unwrap();
         assert_eq!(dt.with_timezone(&Local).year(), now.year());
         assert_eq!(dt.with_timezone(&Local).month(), 5);
         assert_eq!(dt.with_timezone(&Local).day(), 20);
 
-        // Invalid format (-) should be None
-        let dt = parse_full_date_time("2026-02-10", "12:00");
-        assert!(dt.is_none());
+        // YYYY-MM-DD
+        let dt = parse_full_date_time("2026-02-10", "12:00", DisplayZone::Local).unwrap();
+        assert_eq!(dt.with_timezone(&Local).date_naive(), NaiveDate::from_ymd_opt(2026, 2, 10).unwrap());
+
+        // Unknown format should be None
+        assert!(parse_full_date_time("10.02.2026", "12:00", DisplayZone::Local).is_none());
+    }
+
+    #[test]
+    fn test_parse_date_grammar() {
+        // Wednesday
+        let today = NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+        let d = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+
+        let cases = [
+            ("t", d(2026, 3, 4)),
+            ("today", d(2026, 3, 4)),
+            ("tm", d(2026, 3, 5)),
+            ("yesterday", d(2026, 3, 3)),
+            ("yd", d(2026, 3, 3)),
+            ("3d", d(2026, 3, 7)),
+            ("1w", d(2026, 3, 11)),
+            ("+2w", d(2026, 3, 18)),
+            ("-1d", d(2026, 3, 3)),
+            ("2m", d(2026, 5, 4)),
+            ("1y", d(2027, 3, 4)),
+            ("in 3 days", d(2026, 3, 7)),
+            ("in 1 week", d(2026, 3, 11)),
+            ("in 2 months", d(2026, 5, 4)),
+            ("In 1 Year", d(2027, 3, 4)),
+            ("mon", d(2026, 3, 9)),
+            ("wed", d(2026, 3, 11)),
+            ("friday", d(2026, 3, 6)),
+            ("this fri", d(2026, 3, 6)),
+            ("this mon", d(2026, 3, 2)),
+            ("next mon", d(2026, 3, 9)),
+            ("next fri", d(2026, 3, 13)),
+            ("next week", d(2026, 3, 9)),
+            ("next month", d(2026, 4, 1)),
+            ("next year", d(2027, 1, 1)),
+            ("eow", d(2026, 3, 8)),
+            ("eom", d(2026, 3, 31)),
+            ("eoy", d(2026, 12, 31)),
+            ("2026/04/01", d(2026, 4, 1)),
+            ("2026-04-01", d(2026, 4, 1)),
+            ("04/01", d(2026, 4, 1)),
+            ("2026-W10", d(2026, 3, 2)),
+            ("2026-w10-5", d(2026, 3, 6)),
+            ("2026-W01-1", d(2025, 12, 29)),
+            ("", None),
+            ("soon", None),
+            ("in 3", None),
+            ("next thing", None),
+            ("3x", None),
+            ("2026-W54", None),
+            // Offsets past the end of the calendar
+            ("99999999d", None),
+            ("9999999999999999w", None),
+            ("-99999999d", None),
+            ("5000000000m", None),
+            ("999999999999999999y", None),
+            ("in 99999999 days", None),
+            ("2026-W10-8", None),
+            ("2026-02-30", None),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(parse_date_on(input, today), expected, "input: {:?}", input);
+        }
+
+        // End of month clamps to the shorter month
+        let jan31 = NaiveDate::from_ymd_opt(2026, 1, 31).unwrap();
+        assert_eq!(parse_date_on("1m", jan31), d(2026, 2, 28));
+        assert_eq!(parse_date_on("eom", NaiveDate::from_ymd_opt(2028, 2, 10).unwrap()), d(2028, 2, 29));
+    }
+
+    #[test]
+    fn test_parse_japanese_date_time() {
+        // Wednesday
+        let today = NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+        let d = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+
+        let cases = [
+            ("今日", d(2026, 3, 4)),
+            ("明日", d(2026, 3, 5)),
+            ("明後日", d(2026, 3, 6)),
+            ("昨日", d(2026, 3, 3)),
+            ("金曜", d(2026, 3, 6)),
+            ("水曜日", d(2026, 3, 11)),
+            ("今週金曜", d(2026, 3, 6)),
+            ("来週月曜", d(2026, 3, 9)),
+            ("来週の金曜日", d(2026, 3, 13)),
+            ("再来週水", d(2026, 3, 18)),
+            ("3日後", d(2026, 3, 7)),
+            ("３日後", d(2026, 3, 7)),
+            ("2週間後", d(2026, 3, 18)),
+            ("1ヶ月後", d(2026, 4, 4)),
+            ("1年後", d(2027, 3, 4)),
+            ("2日前", d(2026, 3, 2)),
+            ("99999999999999日後", None),
+            ("99999999999999週間後", None),
+            ("99999999999ヶ月後", None),
+            ("999999999999999999年前", None),
+            ("週末", d(2026, 3, 8)),
+            ("月末", d(2026, 3, 31)),
+            ("年末", d(2026, 12, 31)),
+            ("来月", d(2026, 4, 1)),
+            ("2026年3月1日", d(2026, 3, 1)),
+            ("２０２６年４月１日", d(2026, 4, 1)),
+            ("4月10日", d(2026, 4, 10)),
+            ("２０２６／０４／０１", d(2026, 4, 1)),
+            ("2月30日", None),
+            ("3日", None),
+            ("来週火星", None),
+            ("月", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_date_on(input, today), expected, "input: {:?}", input);
+        }
+
+        let t = |h, m| NaiveTime::from_hms_opt(h, m, 0);
+        let times = [
+            ("午後3時", t(15, 0)),
+            ("午前9時半", t(9, 30)),
+            ("午後12時", t(12, 0)),
+            ("午前12時", t(0, 0)),
+            ("15時30分", t(15, 30)),
+            ("１５：００", t(15, 0)),
+            ("夕方", t(18, 0)),
+            ("朝", t(9, 0)),
+            ("25時", None),
+            ("99999999999999h", None),
+            ("3時5", None),
+        ];
+        for (input, expected) in times {
+            assert_eq!(parse_time(input), expected, "input: {:?}", input);
+        }
+
+        let q = parse_quick_add("報告書を送る　明日１５時　＃work　！！！", DisplayZone::Local);
+        assert_eq!(q.title, "報告書を送る");
+        assert_eq!(q.tags, vec!["work"]);
+        assert_eq!(q.priority, Priority::High);
+        let limit = q.limit.unwrap().local(DisplayZone::Local);
+        assert_eq!(limit.date(), Local::now().date_naive() + Duration::days(1));
+        assert_eq!(limit.format("%H:%M").to_string(), "15:00");
+
+        let q = parse_quick_add("歯医者 来週月曜 午後3時", DisplayZone::Local);
+        assert_eq!(q.title, "歯医者");
+        assert_eq!(q.limit.unwrap().local(DisplayZone::Local).format("%H:%M").to_string(), "15:00");
+
+        // 夜 without a date is part of the title
+        let q = parse_quick_add("夜 ごはん", DisplayZone::Local);
+        assert_eq!(q.title, "夜 ごはん");
+        assert_eq!(q.limit, None);
+    }
+
+    #[test]
+    fn test_resolve_local_across_dst() {
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        let naive = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        let utc = |s| Utc.from_utc_datetime(&naive(s));
+
+        assert_eq!(resolve_local(&berlin, naive("2026-07-01 12:00")), utc("2026-07-01 10:00"));
+        assert_eq!(local_time_note(&berlin, naive("2026-07-01 12:00")), None);
+
+        // 02:30 doesn't exist on 29 March; read with the winter offset it is 03:30 summer time
+        assert_eq!(resolve_local(&berlin, naive("2026-03-29 02:30")), utc("2026-03-29 01:30"));
+        assert!(local_time_note(&berlin, naive("2026-03-29 02:30")).is_some());
+
+        // 02:30 happens twice on 25 October; the first one is still summer time
+        assert_eq!(resolve_local(&berlin, naive("2026-10-25 02:30")), utc("2026-10-25 00:30"));
+        assert!(local_time_note(&berlin, naive("2026-10-25 02:30")).is_some());
+
+        let zone = DisplayZone::parse("Europe/Berlin").unwrap();
+        assert_eq!(zone.naive(utc("2026-03-29 01:30")), naive("2026-03-29 03:30"));
+        assert_eq!(DisplayZone::parse("local"), Some(DisplayZone::Local));
+        assert_eq!(DisplayZone::parse("Mars/Olympus"), None);
+        assert_eq!(DeadlineZone::parse("floating"), Some(DeadlineZone::Floating));
+
+        assert_eq!(validate_setting("timezone", " Asia/Tokyo "), Ok("Asia/Tokyo".to_string()));
+        assert!(validate_setting("timezone", "JST").is_err());
+        assert!(validate_setting("colour", "blue").is_err());
+    }
+
+    #[test]
+    fn test_parse_month() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+        let d = |y, m| NaiveDate::from_ymd_opt(y, m, 1);
+        let cases = [
+            ("2026-04", d(2026, 4)),
+            ("2027/1", d(2027, 1)),
+            ("11", d(2026, 11)),
+            ("Mar", d(2026, 3)),
+            ("december", d(2026, 12)),
+            ("３月", d(2026, 3)),
+            ("2025年12月", d(2025, 12)),
+            ("13", None),
+            ("smarch", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_month(input, today), expected, "input: {:?}", input);
+        }
+        assert_eq!(month_days(d(2028, 2).unwrap()).count(), 29);
+    }
+
+    #[test]
+    fn test_parse_priority_and_color() {
+        assert_eq!(parse_priority("High"), Some(Priority::High));
+        assert_eq!(parse_priority("m"), Some(Priority::Medium));
+        assert_eq!(parse_priority("low"), Some(Priority::Low));
+        assert_eq!(parse_priority(""), Some(Priority::None));
+        assert_eq!(parse_priority("none"), Some(Priority::None));
+        assert_eq!(parse_priority("hgh"), None);
+        assert_eq!(parse_priority("bogus"), None);
+
+        assert_eq!(normalize_color("Blue").as_deref(), Some("blue"));
+        assert_eq!(normalize_color("grey").as_deref(), Some("gray"));
+        assert_eq!(normalize_color("#FF8800").as_deref(), Some("#ff8800"));
+        assert_eq!(normalize_color("#ff88"), None);
+        assert_eq!(normalize_color("purple"), None);
+
+        assert_eq!(tag_color(Some("gray")), TagColor::Ansi(8));
+        assert_eq!(tag_color(Some("red")), TagColor::Ansi(1));
+        assert_eq!(tag_color(Some("#ff8800")), TagColor::Rgb(255, 136, 0));
+        assert_eq!(tag_color(None), tag_color(Some("cyan")));
+    }
+
+    #[test]
+    fn test_parse_sort() {
+        let keys = parse_sort("limit, -Created,+title").unwrap();
+        assert_eq!(keys, vec![
+            SortKey { field: SortField::Limit, descending: false },
+            SortKey { field: SortField::Created, descending: true },
+            SortKey { field: SortField::Title, descending: false },
+        ]);
+        assert_eq!(format_sort(&keys), "limit,-created,title");
+        assert!(parse_sort("limit,size").is_err());
+    }
+
+    #[test]
+    fn test_parse_quick_add() {
+        let tomorrow = Local::now().date_naive() + Duration::days(1);
+
+        let q = parse_quick_add("Send report tomorrow 15:00 #work #urgent !high", DisplayZone::Local);
+        assert_eq!(q.title, "Send report");
+        assert_eq!(q.tags, vec!["work", "urgent"]);
+        assert_eq!(q.priority, Priority::High);
+        let limit = q.limit.unwrap().local(DisplayZone::Local);
+        assert_eq!(limit.date(), tomorrow);
+        assert_eq!(limit.format("%H:%M").to_string(), "15:00");
+
+        let q = parse_quick_add("Pay rent tm !! #home/bills", DisplayZone::Local);
+        assert_eq!(q.title, "Pay rent");
+        assert_eq!(q.priority, Priority::Medium);
+        assert_eq!(q.tags, vec!["home/bills"]);
+        assert_eq!(q.limit, Some(Deadline::Day(Local::now().date_naive() + Duration::days(1))));
+
+        // Time words without a date stay in the title; "!" inside a word is text
+        let q = parse_quick_add("Morning run, wow!", DisplayZone::Local);
+        assert_eq!(q.title, "Morning run, wow!");
+        assert_eq!(q.limit, None);
+        assert_eq!(q.priority, Priority::None);
+
+        // Multi-word dates
+        let q = parse_quick_add("Renew passport in 2 months #admin", DisplayZone::Local);
+        assert_eq!(q.title, "Renew passport");
+        assert!(q.limit.is_some());
+
+        // A bare time means today
+        let q = parse_quick_add("Call Bob 18:30", DisplayZone::Local);
+        assert_eq!(q.title, "Call Bob");
+        assert_eq!(q.limit.unwrap().local(DisplayZone::Local).date(), Local::now().date_naive());
+        // Date-like words inside a title stay there
+        for title in ["Watch the sun rise", "Buy 3m tape", "Draw 2d sprites", "Fix 1y old bug", "Review 3/4 of report", "t shirt order"] {
+            let q = parse_quick_add(title, DisplayZone::Local);
+            assert_eq!((q.title.as_str(), q.limit), (title, None), "input: {:?}", title);
+        }
+
+        // A marker sets the date anywhere; quotes keep a trailing date-like word
+        let q = parse_quick_add("Pay @fri rent", DisplayZone::Local);
+        assert_eq!(q.title, "Pay rent");
+        assert_eq!(q.limit, Some(Deadline::Day(parse_date("fri").unwrap())));
+        let q = parse_quick_add("Call due: tomorrow 15:00 the bank", DisplayZone::Local);
+        assert_eq!(q.title, "Call the bank");
+        assert_eq!(q.limit.unwrap().local(DisplayZone::Local).format("%H:%M").to_string(), "15:00");
+        let q = parse_quick_add("Ping @alice", DisplayZone::Local);
+        assert_eq!((q.title.as_str(), q.limit), ("Ping @alice", None));
+        let q = parse_quick_add("Watch the \"sun\" #fun", DisplayZone::Local);
+        assert_eq!((q.title.as_str(), q.limit), ("Watch the sun", None));
+        let q = parse_quick_add("\"#1 fan\" letter tm", DisplayZone::Local);
+        assert_eq!(q.title, "#1 fan letter");
+        assert!(q.tags.is_empty() && q.limit.is_some());
+
+        // A lone date is a title, not a deadline without one
+        assert_eq!(parse_quick_add("tomorrow", DisplayZone::Local).title, "tomorrow");
+
+        // Parts given as flags stay in the title
+        let given = QuickAddGiven { limit: true, tags: true, ..QuickAddGiven::default() };
+        let q = parse_quick_add_except("Notes for mon #work !high", DisplayZone::Local, given);
+        assert_eq!((q.title.as_str(), q.limit, q.tags.len()), ("Notes for mon #work", None, 0));
+        assert_eq!(q.priority, Priority::High);
+        let q = parse_quick_add_except("Call @fri ~1h", DisplayZone::Local, QuickAddGiven { estimate: true, ..given });
+        assert_eq!((q.title.as_str(), q.estimate), ("Call @fri ~1h", None));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30m"), Some(Duration::minutes(30)));
+        assert_eq!(parse_duration("1h30m"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("7h 30m"), Some(Duration::minutes(450)));
+        assert_eq!(parse_duration("1.5h"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("1d"), Some(Duration::minutes(WORKDAY_MINUTES)));
+        assert_eq!(parse_duration("2時間"), Some(Duration::hours(2)));
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("soon"), None);
+        assert_eq!(parse_duration("9999999999999h"), None);
+        assert_eq!(parse_duration(&format!("{}m", "9".repeat(400))), None);
+        assert_eq!(parse_duration("200000d"), None);
+        assert_eq!(parse_duration("876000h"), Some(Duration::hours(876_000)));
+        assert_eq!(format_duration(Duration::minutes(125)), "2h 05m");
+
+        let q = parse_quick_add("Write slides ~2h #work", DisplayZone::Local);
+        assert_eq!(q.title, "Write slides");
+        assert_eq!(q.estimate, Some(Duration::hours(2)));
+    }
+
+    #[test]
+    fn test_hierarchical_tags() {
+        assert_eq!(parse_tags(" work / backend/api , home//,, /"), vec!["work/backend/api", "home"]);
+
+        assert!(tag_matches("work", "work"));
+        assert!(tag_matches("work/backend/api", "work"));
+        assert!(tag_matches("Work/Backend", "work/backend/"));
+        assert!(!tag_matches("homework", "work"));
+        assert!(!tag_matches("workshop", "work"));
+        assert!(!tag_matches("work", "work/backend"));
+
+        let tree = build_tag_tree(&[
+            (1, "work/backend/api".to_string()),
+            (1, "work".to_string()),
+            (2, "work/frontend".to_string()),
+            (3, "work-log".to_string()),
+        ]);
+        let rows: Vec<(&str, usize, usize)> = tree.iter().map(|n| (n.path.as_str(), n.depth, n.count)).collect();
+        assert_eq!(rows, vec![
+            ("work", 0, 2),
+            ("work/backend", 1, 1),
+            ("work/backend/api", 2, 1),
+            ("work/frontend", 1, 1),
+            ("work-log", 0, 1),
+        ]);
     }
 }